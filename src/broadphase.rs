@@ -0,0 +1,150 @@
+use bevy::math::bounding::{Aabb2d, BoundingVolume, IntersectsVolume};
+use std::cmp::Ordering;
+
+/// Every pair of boxes that overlap, each pair once with the lower index first.
+/// Sorts by left edge and sweeps to the right, so only boxes that already
+/// overlap along x get compared. Touching edges count, like `intersects`.
+pub fn overlapping_pairs(boxes: &[Aabb2d]) -> Vec<(usize, usize)> {
+    let mut order: Vec<usize> = (0..boxes.len()).collect();
+    order.sort_unstable_by(|&a, &b| {
+        boxes[a]
+            .min
+            .x
+            .partial_cmp(&boxes[b].min.x)
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let mut pairs = vec![];
+    for (i, &a) in order.iter().enumerate() {
+        for &b in &order[i + 1..] {
+            // Everything further along starts right of this box
+            if boxes[b].min.x > boxes[a].max.x {
+                break;
+            }
+            if boxes[a].intersects(&boxes[b]) {
+                pairs.push((a.min(b), a.max(b)));
+            }
+        }
+    }
+    pairs
+}
+
+/// Items sorted by where their box's center is along x, for finding the ones
+/// near a spot on the road without going through all of them.
+pub struct SortedByX<T> {
+    items: Vec<(f32, T)>,
+}
+
+impl<T> SortedByX<T> {
+    pub fn new(items: impl IntoIterator<Item = (Aabb2d, T)>) -> Self {
+        let mut items: Vec<(f32, T)> = items
+            .into_iter()
+            .map(|(aabb, item)| (aabb.center().x, item))
+            .collect();
+        items.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        Self { items }
+    }
+
+    /// Everything centered at most `reach` away from `x`, left to right.
+    pub fn near(&self, x: f32, reach: f32) -> impl Iterator<Item = &T> {
+        let start = self
+            .items
+            .partition_point(|(center_x, _)| *center_x < x - reach);
+        self.items[start..]
+            .iter()
+            .take_while(move |(center_x, _)| *center_x <= x + reach)
+            .map(|(_, item)| item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::*;
+    use crate::structured_dialog::GameScript;
+    use crate::tuning::GameTuning;
+    use crate::{Car, CarMarker};
+    use bevy::prelude::*;
+    use rand::{Rng, SeedableRng};
+    use std::time::Instant;
+
+    // Cars spread over all four lanes of a road `width` wide, some of them overlapping.
+    fn random_cars(seed: u64, count: usize, width: f32) -> Vec<Aabb2d> {
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+        let lane_height = GameTuning::default().lane_height;
+        (0..count)
+            .map(|_| {
+                let lane = rng.gen_range(0..4) as f32;
+                let y = lane_height / 2. + lane * lane_height - 2. * lane_height;
+                let x = rng.gen_range(-width / 2.0..width / 2.);
+                Aabb2d::new(Vec2::new(x, y), Vec2::new(89. / 2., 53. / 2.))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn broadphase_finds_the_same_pairs_as_checking_every_car() {
+        let cars = random_cars(7, 300, 4000.);
+        let mut expected = vec![];
+        for a in 0..cars.len() {
+            for b in a + 1..cars.len() {
+                if cars[a].intersects(&cars[b]) {
+                    expected.push((a, b));
+                }
+            }
+        }
+        let mut pairs = overlapping_pairs(&cars);
+        pairs.sort();
+        assert!(!expected.is_empty());
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn sorted_cars_near_a_spot_are_the_ones_within_reach() {
+        let cars = random_cars(7, 300, 4000.);
+        let sorted = SortedByX::new(cars.iter().copied().zip(0..));
+        for (x, reach) in [(0., 150.), (-1990., 60.), (1000., 0.), (5000., 100.)] {
+            let mut near: Vec<usize> = sorted.near(x, reach).copied().collect();
+            near.sort();
+            let expected: Vec<usize> = (0..cars.len())
+                .filter(|&i| (cars[i].center().x - x).abs() <= reach)
+                .collect();
+            assert_eq!(near, expected);
+        }
+    }
+
+    // cargo test rush_hour -- --ignored
+    #[test]
+    #[ignore = "benchmark"]
+    fn rush_hour_traffic_fits_in_a_frame() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        stop_spawning(&mut app);
+        // Everything on screen at once, cars off screen get despawned
+        for aabb in random_cars(7, 500, crate::WINDOW_X) {
+            let center = aabb.center();
+            app.world_mut().spawn((
+                CarMarker,
+                Car::new(aabb, 250.),
+                Transform::from_xyz(center.x, center.y, 0.),
+            ));
+        }
+
+        let frames = 300;
+        let started = Instant::now();
+        hold(&mut app, KeyCode::Space, frames);
+        let per_frame = started.elapsed() / frames;
+        assert!(per_frame < FRAME / 4, "500 cars: {:?} per frame", per_frame);
+
+        let overlaps = random_cars(7, 2000, 40000.);
+        let started = Instant::now();
+        for _ in 0..100 {
+            std::hint::black_box(overlapping_pairs(&overlaps));
+        }
+        let per_sweep = started.elapsed() / 100;
+        assert!(
+            per_sweep < FRAME / 10,
+            "2000 cars: {:?} per broadphase",
+            per_sweep
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::difficulty;
+use crate::rng;
+use crate::save;
+use crate::PlayerHealth;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::SystemTime;
+use serde::{Deserialize, Serialize};
+
+pub const DAILY_CHALLENGE_EVENT: &str = "daily_challenge";
+const DAILY_FILE: &str = "daily.json";
+const DAILY_VERSION: u32 = 1;
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// Results of the official daily attempts, stored as `daily.json` next to the
+/// high scores. An attempt is recorded as soon as it starts so quitting
+/// doesn't earn another try:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "results": [
+///     {
+///       "date": "2024-12-24",
+///       "total_earnings": 212.0,
+///       "cycles_completed": 2,
+///       "distance_traveled": 18.5
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Resource, Serialize, Deserialize, Debug, Clone)]
+pub struct DailyResults {
+    pub version: u32,
+    pub results: Vec<DailyResult>,
+}
+
+impl Default for DailyResults {
+    fn default() -> Self {
+        Self {
+            version: DAILY_VERSION,
+            results: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DailyResult {
+    pub date: String,
+    pub total_earnings: f32,
+    pub cycles_completed: u32,
+    pub distance_traveled: f32,
+}
+
+impl DailyResults {
+    pub fn get(&self, date: &str) -> Option<&DailyResult> {
+        self.results.iter().find(|r| r.date == date)
+    }
+
+    /// The `{daily_result}` dialog placeholder for today's official attempt.
+    pub fn summary(&self) -> String {
+        match self.get(&date(today())) {
+            Some(result) => format!("{} {} km", result.total_earnings, result.distance_traveled),
+            None => String::from("---"),
+        }
+    }
+
+    fn record(&mut self, result: DailyResult) {
+        match self.results.iter_mut().find(|r| r.date == result.date) {
+            Some(existing) => *existing = result,
+            None => self.results.push(result),
+        }
+        save::write_json(DAILY_FILE, self);
+    }
+}
+
+/// The daily run in progress, if any. Only the first run of a day is
+/// official, retries after it are practice.
+#[derive(Resource, Default)]
+pub struct DailyChallenge {
+    pub run: Option<DailyRun>,
+}
+
+pub struct DailyRun {
+    pub date: String,
+    pub official: bool,
+}
+
+impl DailyChallenge {
+    pub fn is_active(&self) -> bool {
+        self.run.is_some()
+    }
+
+    /// Starts today's challenge, seeding the run from the date.
+    pub fn start(&mut self, results: &mut DailyResults, run_seed: &mut rng::RunSeed) {
+        self.start_on(today(), results, run_seed);
+    }
+
+    fn start_on(&mut self, day: u64, results: &mut DailyResults, run_seed: &mut rng::RunSeed) {
+        let date = date(day);
+        let official = results.get(&date).is_none();
+        if official {
+            results.record(DailyResult {
+                date: date.clone(),
+                total_earnings: 0.0,
+                cycles_completed: 0,
+                distance_traveled: 0.0,
+            });
+        }
+        run_seed.0 = Some(day);
+        self.run = Some(DailyRun { date, official });
+    }
+
+    /// Records the result of the official attempt. Any run after it is practice.
+    pub fn finish(&mut self, results: &mut DailyResults, player_data: &PlayerHealth) {
+        let Some(run) = &mut self.run else {
+            return;
+        };
+        if run.official {
+            results.record(DailyResult {
+                date: run.date.clone(),
+                total_earnings: player_data.total_earnings,
+                cycles_completed: player_data.cycles_completed,
+                distance_traveled: (player_data.distance_traveled * 100.).round() / 100.,
+            });
+            run.official = false;
+        }
+    }
+}
+
+/// Picks whether the next run from the menu is a normal run or today's
+/// challenge, and at which difficulty.
+#[derive(SystemParam)]
+pub struct RunMode<'w> {
+    challenge: ResMut<'w, DailyChallenge>,
+    results: ResMut<'w, DailyResults>,
+    run_seed: ResMut<'w, rng::RunSeed>,
+    difficulty: ResMut<'w, difficulty::Difficulty>,
+}
+
+impl RunMode<'_> {
+    pub fn normal(&mut self) {
+        self.challenge.run = None;
+        *self.run_seed = rng::RunSeed::default();
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: difficulty::Difficulty) {
+        *self.difficulty = difficulty;
+    }
+
+    /// Everyone plays the daily challenge on Normal so results compare.
+    pub fn daily(&mut self) {
+        self.challenge.start(&mut self.results, &mut self.run_seed);
+        *self.difficulty = difficulty::Difficulty::Normal;
+    }
+}
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load())
+            .insert_resource(DailyChallenge::default());
+    }
+}
+
+fn load() -> DailyResults {
+    let Some(contents) = save::read(DAILY_FILE) else {
+        return DailyResults::default();
+    };
+    match serde_json::from_str::<DailyResults>(&contents) {
+        Ok(results) if results.version == DAILY_VERSION => results,
+        Ok(results) => {
+            warn!("Ignoring daily results with version {}", results.version);
+            DailyResults::default()
+        }
+        Err(e) => {
+            warn!("Ignoring unreadable daily results: {}", e);
+            DailyResults::default()
+        }
+    }
+}
+
+/// Days since 1970-01-01 in UTC, so everyone shares the same day.
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or_default()
+}
+
+/// Formats days since 1970-01-01 as `YYYY-MM-DD`.
+pub fn date(day: u64) -> String {
+    // Howard Hinnant's civil_from_days
+    let z = day as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_since_the_epoch_format_as_dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(59), "1970-03-01");
+        // Leap days, including the one in a century divisible by 400
+        assert_eq!(date(19782), "2024-02-29");
+        assert_eq!(date(19783), "2024-03-01");
+        assert_eq!(date(11016), "2000-02-29");
+        // The turn of the century
+        assert_eq!(date(10956), "1999-12-31");
+        assert_eq!(date(10957), "2000-01-01");
+        // 2100 isn't a leap year
+        assert_eq!(date(47540), "2100-02-28");
+        assert_eq!(date(47541), "2100-03-01");
+    }
+
+    #[test]
+    fn the_run_is_seeded_from_the_day() {
+        let mut results = DailyResults::default();
+        let seed = |day, results: &mut DailyResults| {
+            let mut run_seed = rng::RunSeed(None);
+            DailyChallenge::default().start_on(day, results, &mut run_seed);
+            run_seed.0
+        };
+        assert_eq!(seed(20000, &mut results), Some(20000));
+        assert_eq!(seed(20000, &mut results), seed(20000, &mut results));
+        assert_ne!(seed(20000, &mut results), seed(20001, &mut results));
+    }
+
+    #[test]
+    fn only_the_first_attempt_of_the_day_is_official() {
+        let mut results = DailyResults::default();
+        let mut run_seed = rng::RunSeed(None);
+        let mut challenge = DailyChallenge::default();
+        let mut player_data = PlayerHealth::default();
+
+        // Recorded as soon as it starts, so quitting doesn't earn another try
+        challenge.start_on(20000, &mut results, &mut run_seed);
+        assert!(challenge.run.as_ref().unwrap().official);
+        assert_eq!(results.get("2024-10-04").unwrap().total_earnings, 0.);
+
+        player_data.total_earnings = 120.;
+        challenge.finish(&mut results, &player_data);
+        assert!(!challenge.run.as_ref().unwrap().official);
+        assert_eq!(results.get("2024-10-04").unwrap().total_earnings, 120.);
+
+        // Retries that day are practice and leave the result alone
+        challenge.start_on(20000, &mut results, &mut run_seed);
+        assert!(!challenge.run.as_ref().unwrap().official);
+        player_data.total_earnings = 300.;
+        challenge.finish(&mut results, &player_data);
+        assert_eq!(results.get("2024-10-04").unwrap().total_earnings, 120.);
+
+        // The next day is official again
+        challenge.start_on(20001, &mut results, &mut run_seed);
+        assert!(challenge.run.as_ref().unwrap().official);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::structured_dialog;
+use crate::tuning::GameTuning;
+use crate::AppState;
+use crate::PlayerCar;
+use crate::PlayerMarker;
+use crate::Taxi;
+use bevy::prelude::*;
+
+pub const CRASH_DIALOG: &str = "crash";
+/// How long the crash line stays up, it has no choices to close it with.
+const CRASH_DIALOG_SECONDS: f32 = 2.5;
+
+/// The taxi ran into an NPC car, or one ran into it.
+#[derive(Event, Debug, Clone)]
+pub struct Crash {
+    /// How fast the two cars closed in, in the same units as `speed_x`
+    pub relative_speed: f32,
+}
+
+/// How beaten up the taxi looks. There's no damaged taxi art yet, so each
+/// state tints the sprite a little darker.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DamageState {
+    Fine,
+    Dented,
+    Wrecked,
+}
+
+impl DamageState {
+    pub fn of(damage: f32) -> Self {
+        if damage < 25. {
+            DamageState::Fine
+        } else if damage < 60. {
+            DamageState::Dented
+        } else {
+            DamageState::Wrecked
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            DamageState::Fine => Color::WHITE,
+            DamageState::Dented => Color::srgb(0.8, 0.8, 0.75),
+            DamageState::Wrecked => Color::srgb(0.55, 0.45, 0.4),
+        }
+    }
+}
+
+/// Counts down the crash line, restarted by every crash that shows it.
+#[derive(Resource)]
+struct CrashDialogTimer(Timer);
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Crash>()
+            .insert_resource(CrashDialogTimer(Timer::from_seconds(
+                CRASH_DIALOG_SECONDS,
+                TimerMode::Once,
+            )))
+            .add_systems(
+                Update,
+                (
+                    crash_system,
+                    crash_dialog_timeout_system,
+                    damage_sprite_system,
+                )
+                    .chain()
+                    .after(crate::car_intersection_system)
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+/// Damages the taxi for every crash, a passenger on board lets the driver hear about it.
+fn crash_system(
+    mut crashes: EventReader<Crash>,
+    tuning: Res<GameTuning>,
+    taxi: Res<Taxi>,
+    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
+    dialog_engine: structured_dialog::DialogEngine,
+    mut player_query: Query<&mut PlayerCar, With<PlayerMarker>>,
+    mut crash_dialog_timer: ResMut<CrashDialogTimer>,
+) {
+    for crash in crashes.read() {
+        for mut player_car in player_query.iter_mut() {
+            player_car.damage =
+                (player_car.damage + tuning.crash_damage(crash.relative_speed)).min(100.);
+        }
+
+        let passenger_on_board = taxi.riders().any(|ride| ride.state.is_underway());
+        let choosing = dialog_message
+            .dialog
+            .as_ref()
+            .is_some_and(|dialog| dialog.choices.is_some());
+        if passenger_on_board && !choosing {
+            dialog_message.show(dialog_engine.dialog(CRASH_DIALOG));
+            crash_dialog_timer.0.reset();
+        }
+    }
+}
+
+fn crash_dialog_timeout_system(
+    time: Res<Time>,
+    mut crash_dialog_timer: ResMut<CrashDialogTimer>,
+    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
+) {
+    let showing = dialog_message
+        .dialog
+        .as_ref()
+        .is_some_and(|dialog| dialog.id == CRASH_DIALOG);
+    if showing && crash_dialog_timer.0.tick(time.delta()).just_finished() {
+        dialog_message.show(None);
+    }
+}
+
+fn damage_sprite_system(mut player_query: Query<(&PlayerCar, &mut Sprite), With<PlayerMarker>>) {
+    for (player_car, mut sprite) in player_query.iter_mut() {
+        let color = DamageState::of(player_car.damage).color();
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::*;
+    use crate::structured_dialog::{Dialog, GameScript};
+    use crate::{Car, CarMarker};
+    use bevy::math::bounding::Aabb2d;
+
+    #[test]
+    fn crashing_damages_the_taxi_once_per_car() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        stop_spawning(&mut app);
+
+        // Already rolling when the car shows up, a standing touch does no damage
+        hold(&mut app, KeyCode::Space, 10);
+        let y = -GameTuning::default().lane_height / 2.;
+        app.world_mut().spawn((
+            CarMarker,
+            Car::new(
+                Aabb2d::new(Vec2::new(60., y), Vec2::new(89. / 2., 53. / 2.)),
+                0.,
+            ),
+            Transform::from_xyz(60., y, 0.),
+        ));
+
+        hold(&mut app, KeyCode::Space, 30);
+        let mut player_cars = app.world_mut().query::<&PlayerCar>();
+        let damage = player_cars.single(app.world()).damage;
+        assert!(damage > 0.);
+        assert!(GameTuning::default().damage.top_speed(damage) < 1.);
+
+        // Pushing against the same car doesn't keep adding damage
+        hold(&mut app, KeyCode::Space, 30);
+        assert_eq!(player_cars.single(app.world()).damage, damage);
+    }
+
+    #[test]
+    fn crash_line_goes_away_on_its_own() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        stop_spawning(&mut app);
+        app.world_mut()
+            .resource_mut::<structured_dialog::DialogMessage>()
+            .show(Some(Dialog {
+                id: String::from(CRASH_DIALOG),
+                ..default()
+            }));
+
+        let frames = (CRASH_DIALOG_SECONDS * 60.) as u32;
+        run(&mut app, frames - 10);
+        assert_eq!(dialog_id(&app).as_deref(), Some(CRASH_DIALOG));
+        run(&mut app, 20);
+        assert_eq!(dialog_id(&app), None);
+    }
+}
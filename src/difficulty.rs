@@ -0,0 +1,190 @@
+use crate::tuning::GameTuning;
+use crate::PlayerHealth;
+use crate::ResumeGame;
+use crate::SpawnThingTimer;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Menu action that starts a run at the selected choice's difficulty.
+pub const SET_DIFFICULTY_EVENT: &str = "set_difficulty";
+
+/// The difficulty picked in the main menu for the next run. The run in
+/// progress keeps its own in `PlayerHealth::difficulty`.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// The earnings target never grows, rounds go on as long as it's met
+    Endless,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Endless,
+    ];
+
+    /// Matches the menu choice ids in `dialog.json` and the keys in `tuning.json`.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Endless => "endless",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.id() == id)
+    }
+
+    /// One letter for the high score table, which is already close to the
+    /// width of the textbox. Endless is "X" to tell it apart from Easy.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "E",
+            Difficulty::Normal => "N",
+            Difficulty::Hard => "H",
+            Difficulty::Endless => "X",
+        }
+    }
+}
+
+/// How a difficulty scales the values in `GameTuning`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DifficultyPreset {
+    /// Multiplies `time_limit_seconds`
+    pub time_limit: f32,
+    /// Multiplies every fare
+    pub fares: f32,
+    /// Multiplies how often cars, passengers and shops show up
+    pub traffic: f32,
+    /// Added to every passenger's tip percentage
+    pub tips: f32,
+    /// Keeps the first round's earnings target for the whole run
+    pub flat_target: bool,
+}
+
+impl Default for DifficultyPreset {
+    fn default() -> Self {
+        Self {
+            time_limit: 1.,
+            fares: 1.,
+            traffic: 1.,
+            tips: 0.,
+            flat_target: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Difficulties {
+    pub easy: DifficultyPreset,
+    pub normal: DifficultyPreset,
+    pub hard: DifficultyPreset,
+    pub endless: DifficultyPreset,
+}
+
+impl Default for Difficulties {
+    fn default() -> Self {
+        Self {
+            easy: DifficultyPreset {
+                time_limit: 1.5,
+                fares: 1.25,
+                traffic: 0.75,
+                tips: 5.,
+                ..default()
+            },
+            normal: DifficultyPreset::default(),
+            hard: DifficultyPreset {
+                time_limit: 0.75,
+                fares: 0.9,
+                traffic: 1.5,
+                tips: -5.,
+                ..default()
+            },
+            endless: DifficultyPreset {
+                flat_target: true,
+                ..default()
+            },
+        }
+    }
+}
+
+impl Difficulties {
+    pub fn get(&self, difficulty: Difficulty) -> &DifficultyPreset {
+        match difficulty {
+            Difficulty::Easy => &self.easy,
+            Difficulty::Normal => &self.normal,
+            Difficulty::Hard => &self.hard,
+            Difficulty::Endless => &self.endless,
+        }
+    }
+}
+
+/// Sets up a new run at the difficulty picked in the menu.
+pub fn start_run_system(
+    resume_game: Res<ResumeGame>,
+    difficulty: Res<Difficulty>,
+    tuning: Res<GameTuning>,
+    mut player_data: ResMut<PlayerHealth>,
+    mut spawn_thing_timer: ResMut<SpawnThingTimer>,
+) {
+    if resume_game.resume {
+        return;
+    }
+    *player_data = PlayerHealth::new(&tuning, *difficulty);
+    spawn_thing_timer
+        .timer
+        .set_duration(tuning.spawn_interval(*difficulty));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::*;
+    use crate::structured_dialog::GameScript;
+    use crate::ResetGame;
+
+    #[test]
+    fn difficulty_sets_up_the_run() {
+        let tuning = GameTuning::default();
+        let mut app = headless_app_at(&GameScript::default(), 7, Difficulty::Hard);
+
+        let player_data = app.world().resource::<PlayerHealth>();
+        assert_eq!(player_data.difficulty, Difficulty::Hard);
+        assert!(player_data.time_limit.duration() < tuning.time_limit(Difficulty::Normal));
+        let spawn_interval = app.world().resource::<SpawnThingTimer>().timer.duration();
+        assert!(spawn_interval < tuning.spawn_interval(Difficulty::Normal));
+
+        // Play again keeps the difficulty
+        app.world_mut().resource_mut::<ResetGame>().0 = true;
+        run(&mut app, 1);
+        let player_data = app.world().resource::<PlayerHealth>();
+        assert_eq!(player_data.difficulty, Difficulty::Hard);
+        assert_eq!(
+            player_data.time_limit.duration(),
+            tuning.time_limit(Difficulty::Hard)
+        );
+    }
+
+    #[test]
+    fn endless_keeps_the_first_target() {
+        let tuning = GameTuning::default();
+        assert_eq!(
+            tuning.earnings_target(20, Difficulty::Endless),
+            tuning.earnings_target(0, Difficulty::Normal)
+        );
+        assert!(
+            tuning.earnings_target(20, Difficulty::Normal)
+                > tuning.earnings_target(0, Difficulty::Normal)
+        );
+    }
+}
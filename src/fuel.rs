@@ -0,0 +1,321 @@
+use crate::shop;
+use crate::structured_dialog;
+use crate::structured_dialog::Dialog;
+use crate::tuning::GameTuning;
+use crate::AppState;
+use crate::Controls;
+use crate::CurrentSelection;
+use crate::DisplayLanguage;
+use crate::GasStationMarker;
+use crate::PlayerCar;
+use crate::PlayerHealth;
+use crate::PlayerMarker;
+use crate::RoadsideObject;
+use crate::UiElement;
+use bevy::prelude::*;
+
+pub const GAS_STATION_DIALOG: &str = "gas station";
+pub const TOWED_DIALOG: &str = "towed";
+const FILL_CHOICE: &str = "fill";
+const LEAVE_CHOICE: &str = "leave";
+
+pub struct FuelPlugin;
+
+impl Plugin for FuelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                // Towing goes first so a dry tank in front of a gas station gets towed
+                // instead of stranding the taxi when the player can't pay to fill up
+                (tow_system, gas_station_open_system, fuel_purchase_system).chain(),
+                fuel_gauge_system,
+            )
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+fn fuel_gauge_system(
+    tuning: Res<GameTuning>,
+    display_language: Res<DisplayLanguage>,
+    dialog_engine: structured_dialog::DialogEngine,
+    player_query: Query<&PlayerCar, With<PlayerMarker>>,
+    mut ui_element_query: Query<(&UiElement, &mut TextSpan)>,
+) {
+    let Ok(player_car) = player_query.get_single() else {
+        return;
+    };
+    let fuel = match dialog_engine.dialog("fuel") {
+        Some(dialog) => dialog.language.text(&display_language).to_string(),
+        None => String::from("Fuel"),
+    };
+    let percent = (100. * player_car.fuel / tuning.fuel.tank)
+        .clamp(0., 100.)
+        .ceil();
+
+    for (ui_element, mut text_span) in ui_element_query.iter_mut() {
+        if ui_element.0 == "fuel" {
+            text_span.0 = format!("{}\n{}%", fuel, percent);
+        }
+    }
+}
+
+/// Opens the pump when the taxi stops in the bottom lane in front of a gas station.
+fn gas_station_open_system(
+    tuning: Res<GameTuning>,
+    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
+    dialog_engine: structured_dialog::DialogEngine,
+    player_query: Query<(&Transform, &PlayerCar), With<PlayerMarker>>,
+    station_query: Query<(Entity, &RoadsideObject), With<GasStationMarker>>,
+    mut visited_station: Local<Option<Entity>>,
+) {
+    if dialog_message.dialog.is_some() {
+        return;
+    }
+
+    let Ok((player_transform, player_car)) = player_query.get_single() else {
+        return;
+    };
+    let Some((station_entity, _)) = station_query.iter().find(|(_, roadside_object)| {
+        roadside_object.aabb.min.x < 0. && roadside_object.aabb.max.x > 0.
+    }) else {
+        *visited_station = None;
+        return;
+    };
+
+    // A station the taxi turns back to sells fuel again
+    if player_car.is_moving() {
+        *visited_station = None;
+        return;
+    }
+    if player_transform.translation.y > -100. {
+        return;
+    }
+
+    // Only open once per stop, like the mechanic shop
+    if *visited_station == Some(station_entity) {
+        return;
+    }
+    *visited_station = Some(station_entity);
+
+    let price = tuning.fuel.fill_price(player_car.fuel);
+    dialog_message.show(
+        dialog_engine
+            .dialog(GAS_STATION_DIALOG)
+            .map(|dialog| with_fill_price(dialog, price)),
+    );
+}
+
+// Shows what filling up costs right now next to the fill choice.
+fn with_fill_price(mut dialog: Dialog, price: f32) -> Dialog {
+    for choice in dialog.choices.iter_mut().flatten() {
+        if choice.choice == FILL_CHOICE {
+            choice.dialog.language = shop::priced(&choice.dialog.language, price);
+        }
+    }
+    dialog
+}
+
+/// Fills the tank as far as the total earnings allow.
+fn fuel_purchase_system(
+    controls: Controls,
+    current_selection: Res<CurrentSelection>,
+    tuning: Res<GameTuning>,
+    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
+    dialog_engine: structured_dialog::DialogEngine,
+    mut player_data: ResMut<PlayerHealth>,
+    mut player_query: Query<&mut PlayerCar, With<PlayerMarker>>,
+) {
+    match &dialog_message.dialog {
+        Some(dialog) if dialog.id == GAS_STATION_DIALOG => {}
+        _ => return,
+    }
+
+    if !controls.confirm_just_pressed() {
+        return;
+    }
+
+    if current_selection.0 == LEAVE_CHOICE {
+        dialog_message.show(None);
+        return;
+    }
+    if current_selection.0 != FILL_CHOICE {
+        return;
+    }
+
+    let Ok(mut player_car) = player_query.get_single_mut() else {
+        return;
+    };
+    let fill_price = tuning.fuel.fill_price(player_car.fuel);
+    // Already full, there's nothing to pay for
+    if fill_price <= 0. {
+        dialog_message.show(None);
+        return;
+    }
+    let price = fill_price.min(player_data.total_earnings.max(0.).floor());
+    if price <= 0. {
+        shop::not_enough_money(&mut dialog_message, &dialog_engine);
+        return;
+    }
+
+    player_data.total_earnings -= price;
+    player_data.spent += price;
+    player_car.fuel = (player_car.fuel + price / tuning.fuel.price_per_litre).min(tuning.fuel.tank);
+    dialog_message.show(None);
+}
+
+/// Once the taxi has coasted to a stop on an empty tank, a tow truck leaves a
+/// little fuel and takes its fee out of whatever has been earned.
+fn tow_system(
+    tuning: Res<GameTuning>,
+    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
+    dialog_engine: structured_dialog::DialogEngine,
+    mut player_data: ResMut<PlayerHealth>,
+    mut player_query: Query<&mut PlayerCar, With<PlayerMarker>>,
+) {
+    if let Some(dialog) = &dialog_message.dialog {
+        if dialog.choices.is_some() {
+            return;
+        }
+    }
+
+    let Ok(mut player_car) = player_query.get_single_mut() else {
+        return;
+    };
+    if player_car.fuel > 0. || player_car.speed_coeff > 0. {
+        return;
+    }
+
+    let fee = tuning.fuel.tow_fee.min(player_data.total_earnings.max(0.));
+    player_data.total_earnings -= fee;
+    player_data.spent += fee;
+    player_car.fuel = tuning.fuel.tow_litres;
+    dialog_message.show(dialog_engine.dialog(TOWED_DIALOG));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::*;
+    use crate::PERSON_Y_BOTTOM;
+    use bevy::math::bounding::Aabb2d;
+
+    // Pulls over in front of a gas station with 10 litres left.
+    fn stop_at_the_pump(total_earnings: f32) -> App {
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+        app.world_mut()
+            .resource_mut::<PlayerHealth>()
+            .total_earnings = total_earnings;
+        hold(&mut app, KeyCode::ArrowDown, 20);
+        app.world_mut()
+            .query::<&mut PlayerCar>()
+            .single_mut(app.world_mut())
+            .fuel = 10.;
+
+        let y = PERSON_Y_BOTTOM + 25.;
+        app.world_mut().spawn((
+            GasStationMarker,
+            RoadsideObject {
+                aabb: Aabb2d::new(Vec2::new(0., y), Vec2::new(108., 73.)),
+            },
+            Transform::from_xyz(0., y, 10.),
+        ));
+        run(&mut app, 2);
+        assert_eq!(dialog_id(&app).as_deref(), Some(GAS_STATION_DIALOG));
+        app.world_mut().resource_mut::<CurrentSelection>().0 = String::from(FILL_CHOICE);
+        app
+    }
+
+    #[test]
+    fn filling_up_pays_for_what_the_tank_takes() {
+        let tuning = GameTuning::default();
+        let mut app = stop_at_the_pump(100.);
+        hold(&mut app, KeyCode::Enter, 1);
+
+        assert_eq!(dialog_id(&app), None);
+        let fuel = app
+            .world_mut()
+            .query::<&PlayerCar>()
+            .single(app.world())
+            .fuel;
+        assert_eq!(fuel, tuning.fuel.tank);
+        let price = tuning.fuel.fill_price(10.);
+        let player_data = app.world().resource::<PlayerHealth>();
+        assert_eq!(player_data.total_earnings, 100. - price);
+        assert_eq!(player_data.spent, price);
+    }
+
+    #[test]
+    fn broke_drivers_are_told_at_the_pump() {
+        let mut app = stop_at_the_pump(0.);
+        hold(&mut app, KeyCode::Enter, 1);
+
+        let dialog = app
+            .world()
+            .resource::<structured_dialog::DialogMessage>()
+            .dialog
+            .clone()
+            .unwrap();
+        assert_eq!(dialog.id, GAS_STATION_DIALOG);
+        assert!(dialog.language.text("en").starts_with("Not enough coins"));
+        let fuel = app
+            .world_mut()
+            .query::<&PlayerCar>()
+            .single(app.world())
+            .fuel;
+        assert_eq!(fuel, 10.);
+    }
+
+    #[test]
+    fn turning_back_to_the_same_station_opens_the_pump_again() {
+        let tuning = GameTuning::default();
+        let mut app = stop_at_the_pump(100.);
+        app.world_mut().resource_mut::<CurrentSelection>().0 = String::from(LEAVE_CHOICE);
+        hold(&mut app, KeyCode::Enter, 1);
+        assert_eq!(dialog_id(&app), None);
+
+        // Pulls away, swings around and rolls back to a stop at the same pump
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        run(&mut app, 10);
+        hold(&mut app, KeyCode::ArrowLeft, 1);
+        run(&mut app, (tuning.u_turn.seconds * 60.) as u32 + 1);
+        assert_eq!(dialog_id(&app), None);
+        release(&mut app, KeyCode::Space);
+        run(&mut app, 60);
+
+        assert_eq!(dialog_id(&app).as_deref(), Some(GAS_STATION_DIALOG));
+    }
+
+    #[test]
+    fn running_dry_coasts_to_a_stop_and_calls_a_tow() {
+        let tuning = GameTuning::default();
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+        app.world_mut()
+            .resource_mut::<PlayerHealth>()
+            .total_earnings = 100.;
+
+        hold(&mut app, KeyCode::Space, 30);
+        let mut player_cars = app.world_mut().query::<&mut PlayerCar>();
+        let mut player_car = player_cars.single_mut(app.world_mut());
+        assert!(player_car.fuel < tuning.fuel.tank);
+        let speed = player_car.speed_coeff;
+        player_car.fuel = 0.;
+
+        // The gas pedal does nothing on an empty tank
+        hold(&mut app, KeyCode::Space, 10);
+        let player_car = player_cars.single(app.world());
+        assert!(player_car.speed_coeff > 0. && player_car.speed_coeff < speed);
+
+        run(&mut app, 60);
+        assert_eq!(dialog_id(&app).as_deref(), Some(TOWED_DIALOG));
+        let player_car = player_cars.single(app.world());
+        assert_eq!(player_car.fuel, tuning.fuel.tow_litres);
+        let player_data = app.world().resource::<PlayerHealth>();
+        assert_eq!(player_data.total_earnings, 100. - tuning.fuel.tow_fee);
+    }
+}
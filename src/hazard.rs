@@ -0,0 +1,118 @@
+use crate::tuning::GameTuning;
+use crate::AppState;
+use crate::GameState;
+use crate::PlayerCar;
+use crate::PlayerMarker;
+use crate::RoadsideObject;
+use crate::Taxi;
+use bevy::math::bounding::{Aabb2d, BoundingVolume, IntersectsVolume};
+use bevy::prelude::*;
+
+/// A puddle in a lane. It scrolls with the road as a `RoadsideObject` and
+/// only makes the taxi skid the first time it's driven over.
+#[derive(Component, Default)]
+pub struct OilPuddle {
+    pub hit: bool,
+}
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, oil_puddle_system.run_if(in_state(AppState::Game)));
+    }
+}
+
+pub fn spawn_oil_puddle(commands: &mut Commands, asset_server: &AssetServer, position: Vec2) {
+    commands.spawn((
+        GameState,
+        OilPuddle::default(),
+        RoadsideObject {
+            aabb: Aabb2d::new(position, Vec2::new(51. / 2., 34. / 2.)),
+        },
+        Sprite {
+            image: asset_server.load("oilpuddle.png"),
+            ..default()
+        },
+        Transform::from_xyz(position.x, position.y, 0.),
+    ));
+}
+
+/// Driving over oil throws the taxi into the next lane and leaves it sliding,
+/// see `movement_input_system`, and the passenger takes it out of the tip.
+fn oil_puddle_system(
+    tuning: Res<GameTuning>,
+    mut taxi: ResMut<Taxi>,
+    mut player_query: Query<(&mut Transform, &mut PlayerCar), With<PlayerMarker>>,
+    mut puddle_query: Query<(&RoadsideObject, &mut OilPuddle)>,
+) {
+    let Ok((mut player_transform, mut player_car)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for (roadside_object, mut puddle) in puddle_query.iter_mut() {
+        if puddle.hit || !player_car.aabb.intersects(&roadside_object.aabb) {
+            continue;
+        }
+        puddle.hit = true;
+        player_car.skid = tuning.oil.skid_seconds;
+
+        // Slide away from the center line when there's a lane to slide into,
+        // otherwise back towards it
+        let lane_height = tuning.lane_height;
+        let y = player_transform.translation.y;
+        let drift = if y > 0. {
+            if y < lane_height {
+                lane_height
+            } else {
+                -lane_height
+            }
+        } else if y > -lane_height {
+            -lane_height
+        } else {
+            lane_height
+        };
+        player_transform.translation.y += drift;
+        player_car.aabb.translate_by(Vec2::new(0.0, drift));
+
+        for ride in taxi.riders_mut().filter(|ride| ride.state.is_underway()) {
+            ride.tip_percentage -= tuning.oil.tip_loss;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::*;
+    use crate::structured_dialog::GameScript;
+
+    #[test]
+    fn oil_puddle_throws_the_taxi_into_the_next_lane() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        stop_spawning(&mut app);
+
+        let start_y = player_y(&mut app);
+        app.world_mut().spawn((
+            OilPuddle::default(),
+            RoadsideObject {
+                aabb: Aabb2d::new(Vec2::new(80., start_y), Vec2::new(51. / 2., 34. / 2.)),
+            },
+            Transform::from_xyz(80., start_y, 0.),
+        ));
+
+        hold(&mut app, KeyCode::Space, 30);
+        assert_eq!(
+            player_y(&mut app),
+            start_y - GameTuning::default().lane_height
+        );
+        let mut player_cars = app.world_mut().query::<&PlayerCar>();
+        let player_car = player_cars.single(app.world());
+        assert!(player_car.skid > 0.);
+
+        // No brakes while sliding
+        let speed = player_car.speed_coeff;
+        run(&mut app, 5);
+        assert_eq!(player_cars.single(app.world()).speed_coeff, speed);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::difficulty;
+use crate::save;
+use crate::structured_dialog;
+use crate::structured_dialog::{Actions, Choice, ChoiceDialog, Dialog, Language};
+use crate::AppState;
+use crate::Controls;
+use crate::CurrentSelection;
+use crate::PlayerHealth;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+pub const LEADERBOARD_SIZE: usize = 10;
+pub const NAME_ENTRY_DIALOG: &str = "high score";
+pub const NAME_LETTER_DIALOG: &str = "high score letter";
+const LEADERBOARD_FILE: &str = "scores.json";
+const LEADERBOARD_VERSION: u32 = 1;
+const MAX_NAME_LENGTH: usize = 8;
+const LETTER_GROUPS: [&str; 5] = ["ABCDEF", "GHIJKL", "MNOPQR", "STUVWX", "YZ"];
+
+/// The high score table, stored as `scores.json` next to the save file
+/// (or under the `taxigt-scores.json` localStorage key on the web):
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "entries": [
+///     {
+///       "name": "ADA",
+///       "total_earnings": 412.0,
+///       "cycles_completed": 4,
+///       "distance_traveled": 37.25,
+///       "difficulty": "normal"
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Entries are kept best first: highest `total_earnings`, then the longest
+/// `distance_traveled` on a tie. At most `LEADERBOARD_SIZE` are kept.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone)]
+pub struct Leaderboard {
+    pub version: u32,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Self {
+            version: LEADERBOARD_VERSION,
+            entries: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub total_earnings: f32,
+    pub cycles_completed: u32,
+    pub distance_traveled: f32,
+    /// Entries from before difficulties were added count as Normal
+    #[serde(default)]
+    pub difficulty: difficulty::Difficulty,
+}
+
+impl LeaderboardEntry {
+    fn from_run(name: String, player_data: &PlayerHealth) -> Self {
+        Self {
+            name,
+            total_earnings: player_data.total_earnings,
+            cycles_completed: player_data.cycles_completed,
+            distance_traveled: (player_data.distance_traveled * 100.).round() / 100.,
+            difficulty: player_data.difficulty,
+        }
+    }
+
+    fn ranks_above(&self, other: &LeaderboardEntry) -> bool {
+        self.total_earnings > other.total_earnings
+            || (self.total_earnings == other.total_earnings
+                && self.distance_traveled > other.distance_traveled)
+    }
+}
+
+impl Leaderboard {
+    pub fn qualifies(&self, player_data: &PlayerHealth) -> bool {
+        if player_data.total_earnings <= 0.0 {
+            return false;
+        }
+        if self.entries.len() < LEADERBOARD_SIZE {
+            return true;
+        }
+        let entry = LeaderboardEntry::from_run(String::new(), player_data);
+        self.entries.iter().any(|e| entry.ranks_above(e))
+    }
+
+    pub fn record(&mut self, entry: LeaderboardEntry) {
+        let position = self
+            .entries
+            .iter()
+            .position(|e| entry.ranks_above(e))
+            .unwrap_or(self.entries.len());
+        self.entries.insert(position, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        save::write_json(LEADERBOARD_FILE, self);
+    }
+
+    /// The table used for the `{high_scores}` dialog placeholder.
+    pub fn table(&self) -> String {
+        if self.entries.is_empty() {
+            return String::from("---");
+        }
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(index, e)| {
+                format!(
+                    "{:>2}. {:<8} {:>6} {:>6} km {}",
+                    index + 1,
+                    e.name,
+                    e.total_earnings,
+                    e.distance_traveled,
+                    e.difficulty.label()
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// The name being typed in for a new high score.
+#[derive(Resource, Default)]
+pub struct NameEntry {
+    pub name: String,
+    /// Set once the finished run was offered a spot, until the next reset.
+    pub recorded: bool,
+}
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load())
+            .insert_resource(NameEntry::default())
+            .add_systems(
+                Update,
+                // After the choice system, so the Enter that finishes the name
+                // doesn't also close the game over dialog that replaces it
+                name_entry_system
+                    .after(crate::dialog_choice_selection_system)
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+fn load() -> Leaderboard {
+    let Some(contents) = save::read(LEADERBOARD_FILE) else {
+        return Leaderboard::default();
+    };
+    match serde_json::from_str::<Leaderboard>(&contents) {
+        Ok(leaderboard) if leaderboard.version == LEADERBOARD_VERSION => leaderboard,
+        Ok(leaderboard) => {
+            warn!("Ignoring high scores with version {}", leaderboard.version);
+            Leaderboard::default()
+        }
+        Err(e) => {
+            warn!("Ignoring unreadable high scores: {}", e);
+            Leaderboard::default()
+        }
+    }
+}
+
+pub fn is_name_entry(dialog: &Dialog) -> bool {
+    dialog.id == NAME_ENTRY_DIALOG || dialog.id == NAME_LETTER_DIALOG
+}
+
+/// The dialog shown when a run ends, asking for a name if it made the table.
+pub fn game_over_dialog(
+    dialog_engine: &structured_dialog::DialogEngine,
+    leaderboard: &Leaderboard,
+    name_entry: &mut NameEntry,
+    player_data: &PlayerHealth,
+) -> Dialog {
+    if !name_entry.recorded && leaderboard.qualifies(player_data) {
+        name_entry.name = String::new();
+        if let Some(dialog) = dialog_engine.dialog(NAME_ENTRY_DIALOG) {
+            return with_name(dialog, &name_entry.name, group_choices(&name_entry.name));
+        }
+    }
+    name_entry.recorded = true;
+    dialog_engine.game_over()
+}
+
+fn choice(id: &str, text: &str) -> Choice {
+    Choice {
+        choice: id.to_string(),
+        dialog: ChoiceDialog {
+            language: Language(HashMap::from_iter([(
+                String::from(structured_dialog::DEFAULT_LOCALE),
+                text.to_string(),
+            )])),
+            actions: Actions::default(),
+        },
+    }
+}
+
+// Letters are picked in two steps, a group and then a letter in it, so the
+// choices fit in the dialog textbox.
+fn group_choices(name: &str) -> Vec<Choice> {
+    let mut choices = vec![];
+    if name.len() < MAX_NAME_LENGTH {
+        for group in LETTER_GROUPS {
+            let first = &group[..1];
+            let last = &group[group.len() - 1..];
+            choices.push(choice(group, &format!("{}-{}", first, last)));
+        }
+    }
+    if !name.is_empty() {
+        choices.push(choice("delete", "<-"));
+        choices.push(choice("done", "OK"));
+    }
+    choices
+}
+
+fn letter_choices(group: &str) -> Vec<Choice> {
+    let mut choices: Vec<Choice> = group
+        .chars()
+        .map(|letter| choice(&letter.to_string(), &letter.to_string()))
+        .collect();
+    choices.push(choice("back", "<-"));
+    choices
+}
+
+fn with_name(mut dialog: Dialog, name: &str, choices: Vec<Choice>) -> Dialog {
+    for text in dialog.language.0.values_mut() {
+        *text = text.replace("{name}", &format!("{}_", name));
+    }
+    dialog.choices = Some(choices);
+    dialog
+}
+
+fn name_entry_system(
+    controls: Controls,
+    current_selection: Res<CurrentSelection>,
+    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
+    dialog_engine: structured_dialog::DialogEngine,
+    mut leaderboard: ResMut<Leaderboard>,
+    mut name_entry: ResMut<NameEntry>,
+    player_data: Res<PlayerHealth>,
+) {
+    match &dialog_message.dialog {
+        Some(dialog) if is_name_entry(dialog) => {}
+        _ => return,
+    }
+
+    if !controls.confirm_just_pressed() {
+        return;
+    }
+
+    let selection = current_selection.0.as_str();
+    let (id, choices) = match selection {
+        "done" => {
+            leaderboard.record(LeaderboardEntry::from_run(
+                name_entry.name.clone(),
+                &player_data,
+            ));
+            name_entry.recorded = true;
+            dialog_message.show(Some(dialog_engine.game_over()));
+            return;
+        }
+        "delete" => {
+            name_entry.name.pop();
+            (NAME_ENTRY_DIALOG, group_choices(&name_entry.name))
+        }
+        "back" => (NAME_ENTRY_DIALOG, group_choices(&name_entry.name)),
+        group if LETTER_GROUPS.contains(&group) => (NAME_LETTER_DIALOG, letter_choices(group)),
+        letter if letter.len() == 1 => {
+            name_entry.name.push_str(letter);
+            (NAME_ENTRY_DIALOG, group_choices(&name_entry.name))
+        }
+        _ => return,
+    };
+
+    if let Some(dialog) = dialog_engine.dialog(id) {
+        dialog_message.show(Some(with_name(dialog, &name_entry.name, choices)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn entry(name: &str, total_earnings: f32, distance_traveled: f32) -> LeaderboardEntry {
+        LeaderboardEntry {
+            name: name.to_string(),
+            total_earnings,
+            cycles_completed: 1,
+            distance_traveled,
+            difficulty: difficulty::Difficulty::Normal,
+        }
+    }
+
+    fn names(leaderboard: &Leaderboard) -> Vec<&str> {
+        leaderboard
+            .entries
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect()
+    }
+
+    fn run_earning(total_earnings: f32, distance_traveled: f32) -> PlayerHealth {
+        PlayerHealth {
+            total_earnings,
+            distance_traveled,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn ties_in_earnings_go_to_the_longer_drive() {
+        assert!(entry("A", 20., 1.).ranks_above(&entry("B", 10., 5.)));
+        assert!(entry("A", 10., 5.).ranks_above(&entry("B", 10., 1.)));
+        assert!(!entry("A", 10., 1.).ranks_above(&entry("B", 10., 5.)));
+        assert!(!entry("A", 10., 5.).ranks_above(&entry("B", 10., 5.)));
+    }
+
+    #[test]
+    fn recorded_runs_are_ranked_and_the_table_capped() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record(entry("B", 20., 1.));
+        leaderboard.record(entry("D", 10., 1.));
+        leaderboard.record(entry("A", 30., 1.));
+        leaderboard.record(entry("C", 20., 0.5));
+        // Equal runs go below the ones already there
+        leaderboard.record(entry("E", 10., 1.));
+        assert_eq!(names(&leaderboard), ["A", "B", "C", "D", "E"]);
+
+        for n in 0..LEADERBOARD_SIZE {
+            leaderboard.record(entry(&format!("{}", n), 5. - n as f32, 1.));
+        }
+        assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+        assert_eq!(
+            names(&leaderboard),
+            ["A", "B", "C", "D", "E", "0", "1", "2", "3", "4"]
+        );
+
+        leaderboard.record(entry("F", 25., 1.));
+        assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+        assert_eq!(names(&leaderboard)[1], "F");
+        assert_eq!(names(&leaderboard).last(), Some(&"3"));
+    }
+
+    #[test]
+    fn runs_qualify_while_there_is_room_or_they_beat_an_entry() {
+        let mut leaderboard = Leaderboard::default();
+        assert!(!leaderboard.qualifies(&run_earning(0., 10.)));
+        assert!(leaderboard.qualifies(&run_earning(1., 0.)));
+
+        for n in 0..LEADERBOARD_SIZE - 1 {
+            leaderboard.record(entry(&format!("{}", n), 100., 10.));
+        }
+        assert!(leaderboard.qualifies(&run_earning(1., 0.)));
+
+        leaderboard.record(entry("last", 50., 10.));
+        assert!(!leaderboard.qualifies(&run_earning(0., 10.)));
+        assert!(!leaderboard.qualifies(&run_earning(40., 10.)));
+        assert!(!leaderboard.qualifies(&run_earning(50., 10.)));
+        assert!(leaderboard.qualifies(&run_earning(50., 11.)));
+        assert!(leaderboard.qualifies(&run_earning(60., 0.)));
+    }
+
+    fn pick(app: &mut App, choice: &str) {
+        app.world_mut().resource_mut::<CurrentSelection>().0 = String::from(choice);
+        hold(app, KeyCode::Enter, 1);
+    }
+
+    fn choices(app: &App) -> Vec<String> {
+        let dialog_message = app.world().resource::<structured_dialog::DialogMessage>();
+        let dialog = dialog_message.dialog.as_ref().unwrap();
+        dialog
+            .choices
+            .iter()
+            .flatten()
+            .map(|c| c.choice.clone())
+            .collect()
+    }
+
+    #[test]
+    fn names_are_typed_a_letter_at_a_time() {
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+        app.add_systems(
+            Update,
+            name_entry_system
+                .after(crate::dialog_choice_selection_system)
+                .run_if(in_state(AppState::Game)),
+        );
+        app.world_mut()
+            .resource_mut::<PlayerHealth>()
+            .total_earnings = 42.;
+        app.world_mut()
+            .run_system_once(
+                |dialog_engine: structured_dialog::DialogEngine,
+                 leaderboard: Res<Leaderboard>,
+                 mut name_entry: ResMut<NameEntry>,
+                 player_data: Res<PlayerHealth>,
+                 mut dialog_message: ResMut<structured_dialog::DialogMessage>| {
+                    let dialog = game_over_dialog(
+                        &dialog_engine,
+                        &leaderboard,
+                        &mut name_entry,
+                        &player_data,
+                    );
+                    dialog_message.show(Some(dialog));
+                },
+            )
+            .unwrap();
+        assert_eq!(dialog_id(&app).as_deref(), Some(NAME_ENTRY_DIALOG));
+        // Nothing to delete or confirm yet
+        assert_eq!(choices(&app), LETTER_GROUPS);
+
+        pick(&mut app, "ABCDEF");
+        assert_eq!(dialog_id(&app).as_deref(), Some(NAME_LETTER_DIALOG));
+        assert_eq!(choices(&app), ["A", "B", "C", "D", "E", "F", "back"]);
+        pick(&mut app, "back");
+        assert_eq!(dialog_id(&app).as_deref(), Some(NAME_ENTRY_DIALOG));
+        assert_eq!(app.world().resource::<NameEntry>().name, "");
+
+        pick(&mut app, "ABCDEF");
+        pick(&mut app, "A");
+        pick(&mut app, "ABCDEF");
+        pick(&mut app, "D");
+        pick(&mut app, "ABCDEF");
+        pick(&mut app, "B");
+        assert_eq!(app.world().resource::<NameEntry>().name, "ADB");
+        assert_eq!(choices(&app).last().map(String::as_str), Some("done"));
+
+        pick(&mut app, "delete");
+        pick(&mut app, "ABCDEF");
+        pick(&mut app, "A");
+        assert_eq!(app.world().resource::<NameEntry>().name, "ADA");
+        let dialog_message = app.world().resource::<structured_dialog::DialogMessage>();
+        assert!(dialog_message.dialog.as_ref().unwrap().language.0
+            [structured_dialog::DEFAULT_LOCALE]
+            .contains("ADA_"));
+
+        pick(&mut app, "done");
+        assert_eq!(
+            dialog_id(&app).as_deref(),
+            Some(structured_dialog::GAME_OVER_DIALOG)
+        );
+        assert!(app.world().resource::<NameEntry>().recorded);
+        let leaderboard = app.world().resource::<Leaderboard>();
+        assert_eq!(names(leaderboard), ["ADA"]);
+        assert_eq!(leaderboard.entries[0].total_earnings, 42.);
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::asset::AssetMetaCheck;
+use bevy::ecs::system::SystemParam;
 use bevy::{
     audio::{PlaybackMode, Volume},
     math::bounding::{Aabb2d, BoundingVolume, IntersectsVolume},
@@ -7,22 +8,41 @@ use bevy::{
 };
 use bevy_common_assets::json::JsonAssetPlugin;
 use rand::Rng;
+use std::time::Duration;
 use util::window::PixelScale;
 
+mod broadphase;
+mod daily;
+mod damage;
+mod difficulty;
+mod fuel;
+mod hazard;
+mod leaderboard;
 mod menu;
+mod meter;
 mod names;
+mod patience;
+mod personality;
+mod places;
+mod rating;
+mod receipt;
+mod ride;
+mod rng;
+mod route;
+mod save;
+mod shop;
+mod simulation;
 mod splash;
 mod structured_dialog;
+mod traffic;
+mod tuning;
 mod util;
 
 const WINDOW_Y: f32 = 480.;
 const WINDOW_X: f32 = 640.;
-const SPEED_X: f32 = 300.;
-const LANE_HEIGHT: f32 = 70.;
 const HALF_CAR_WIDTH: f32 = 89. / 2.;
 const PERSON_Y_TOP: f32 = 160.;
 const PERSON_Y_BOTTOM: f32 = -160.;
-const TIME_LIMIT_SECONDS: f32 = 60.0;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
 pub enum AppState {
@@ -46,7 +66,7 @@ pub enum SpeedSfx {
 }
 
 #[derive(Resource, Deref, DerefMut)]
-pub struct DisplayLanguage(pub &'static str);
+pub struct DisplayLanguage(pub String);
 
 #[derive(Component)]
 pub struct GameState;
@@ -69,35 +89,121 @@ pub struct DialogTextboxChoiceMarker;
 pub struct Passenger {
     name: String,
     sprite_index: usize,
+    personality: personality::Personality,
 }
 
 #[derive(Resource)]
 pub struct InteractionRateLimit(pub Timer);
 
+/// The keyboard and the first connected gamepad.
+#[derive(SystemParam)]
+pub struct Controls<'w, 's> {
+    pub keyboard: Res<'w, ButtonInput<KeyCode>>,
+    pub gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl Controls<'_, '_> {
+    /// E or Enter, or any face button.
+    pub fn confirm_just_pressed(&self) -> bool {
+        let button = self.gamepads.iter().next().is_some_and(|gamepad| {
+            gamepad.any_just_pressed([
+                GamepadButton::North,
+                GamepadButton::South,
+                GamepadButton::East,
+                GamepadButton::West,
+            ])
+        });
+        button
+            || self
+                .keyboard
+                .any_just_pressed([KeyCode::KeyE, KeyCode::Enter])
+    }
+}
+
+/// What a new run starts over with, see `reset`.
+#[derive(SystemParam)]
+pub struct RunState<'w> {
+    dialog_message: ResMut<'w, structured_dialog::DialogMessage>,
+    player_data: ResMut<'w, PlayerHealth>,
+    taxi: ResMut<'w, Taxi>,
+    reputation: ResMut<'w, patience::Reputation>,
+    current_selection: ResMut<'w, CurrentSelection>,
+    occured_events: ResMut<'w, OccuredEvents>,
+    posessions: ResMut<'w, Posessions>,
+    name_entry: ResMut<'w, leaderboard::NameEntry>,
+    game_rng: ResMut<'w, rng::GameRng>,
+    run_seed: Res<'w, rng::RunSeed>,
+    tuning: Res<'w, tuning::GameTuning>,
+}
+
 #[derive(Component)]
 pub struct SelectionMarker(pub String);
 
 #[derive(Component, Default)]
 struct Intersects;
 
+/// Most passengers the dashboard has room to show.
+pub const MAX_SEATS: usize = 4;
+
 #[derive(Resource, Default)]
 pub struct Taxi {
     pub rides: Vec<Ride>,
     pub closest_person: Option<Entity>,
-    pub current_rider: Option<Entity>,
+    /// Everyone in the taxi, in the order they got in
+    pub riders: Vec<Entity>,
+}
+
+impl Taxi {
+    pub fn riders(&self) -> impl Iterator<Item = &Ride> {
+        self.rides
+            .iter()
+            .filter(|ride| self.riders.contains(&ride.who))
+    }
+
+    pub fn riders_mut(&mut self) -> impl Iterator<Item = &mut Ride> {
+        let riders = &self.riders;
+        self.rides
+            .iter_mut()
+            .filter(|ride| riders.contains(&ride.who))
+    }
 }
 
 pub struct Ride {
     pub who: Entity,
     pub passenger: Passenger,
-    pub accepted: Option<bool>,
+    pub state: ride::RideState,
+    /// Where the passenger is headed, see `places::place_name`
+    pub destination: String,
+    /// Which way the destination is from the pickup, see `route::heading`
+    pub heading: f32,
     pub distance: f32,
-    pub completed: bool,
+    /// Km driven toward the destination, driving away takes them back off
+    pub traveled: f32,
+    /// How far ahead of the taxi the destination's landmark is once it's
+    /// been put up, negative once it's been passed
+    pub stop_ahead: Option<f32>,
+    /// The quote until the passenger pays, then what the meter read
     pub trip_cost: f32,
+    pub meter: meter::FareMeter,
+    /// Only offered to well rated drivers, pays `tuning::RatingTuning::vip_fares` times the fare
+    pub vip: bool,
+    /// Shared the taxi at some point, `trip_cost` is already discounted
+    pub pooled: bool,
     pub tip_percentage: f32,
     pub tip: f32,
     pub distance_past_dropoff: f32,
     pub trip_time: f32,
+    /// How the tip was worked out, once the passenger has paid
+    pub receipt: Option<receipt::Receipt>,
+}
+
+impl Ride {
+    pub fn pool(&mut self, pooling: &tuning::PoolingTuning) {
+        if !self.pooled {
+            self.pooled = true;
+            self.trip_cost = pooling.pooled_fare(self.trip_cost);
+        }
+    }
 }
 
 #[derive(Resource, Debug, Clone, Deref, DerefMut)]
@@ -125,27 +231,32 @@ pub struct PlayerHealth {
     pub time_limit: Timer,
     pub cycles_completed: u32,
     pub distance_traveled: f32,
+    pub difficulty: difficulty::Difficulty,
 }
 
-impl Default for PlayerHealth {
-    fn default() -> Self {
+impl PlayerHealth {
+    pub fn new(tuning: &tuning::GameTuning, difficulty: difficulty::Difficulty) -> Self {
         Self {
-            time_limit_required_earnings: 50.,
-            time_limit: Timer::from_seconds(TIME_LIMIT_SECONDS, TimerMode::Once),
+            time_limit_required_earnings: tuning.earnings_target(0, difficulty),
+            time_limit: Timer::new(tuning.time_limit(difficulty), TimerMode::Once),
             level: 0.0,
             total_earnings: 0.0,
             spent: 0.0,
             earnings: 0.0,
             cycles_completed: 0,
             distance_traveled: 0.0,
+            difficulty,
         }
     }
 }
 
-#[derive(Resource, Default)]
-pub struct Travel {
-    distance: f32,
-    traveled: f32,
+impl Default for PlayerHealth {
+    fn default() -> Self {
+        Self::new(
+            &tuning::GameTuning::default(),
+            difficulty::Difficulty::default(),
+        )
+    }
 }
 
 #[derive(Component)]
@@ -157,16 +268,37 @@ pub struct RoadMarker;
 #[derive(Component)]
 pub struct CarMarker;
 
+/// One seat on the dashboard, showing whoever sits there.
 #[derive(Component)]
-pub struct PersonInCarMarker;
+pub struct PersonInCarMarker(usize);
 
 #[derive(Component, Clone)]
 pub struct Car {
     pub aabb: Aabb2d,
     pub speed: f32,
+    /// The speed the driver goes back up to once the road ahead is clear
+    pub cruise_speed: f32,
+    pub lane_change: Option<traffic::LaneChange>,
     pub intersects_player: bool,
     pub intersects_npc: bool,
     pub blocks_player_movement: bool,
+    /// Set once this car has hit the taxi, so each car only damages it once
+    pub crashed: bool,
+}
+
+impl Car {
+    pub fn new(aabb: Aabb2d, speed: f32) -> Self {
+        Self {
+            aabb,
+            speed,
+            cruise_speed: speed,
+            lane_change: None,
+            intersects_player: false,
+            intersects_npc: false,
+            blocks_player_movement: false,
+            crashed: false,
+        }
+    }
 }
 
 #[derive(Component)]
@@ -193,6 +325,36 @@ pub struct PlayerCar {
     pub rate_limit_down: Timer,
     pub atlas_left: (usize, usize),
     pub atlas_right: (usize, usize),
+    // Tuned by the mechanic shop upgrades
+    pub acceleration: f32,
+    pub braking: f32,
+    pub top_speed: f32,
+    pub comfort: f32,
+    /// Litres left, the gas pedal does nothing once it hits 0
+    pub fuel: f32,
+    /// 0 for a new taxi, 100 for a wreck. Fixed at the mechanic shop
+    pub damage: f32,
+    /// Seconds left sliding on oil, the taxi can't steer or brake until then
+    pub skid: f32,
+    pub u_turn: Option<route::UTurn>,
+}
+
+impl PlayerCar {
+    pub fn reset_upgrades(&mut self) {
+        self.acceleration = 1.0;
+        self.braking = 1.0;
+        self.top_speed = 1.0;
+        self.comfort = 0.0;
+        self.rate_limit_up
+            .set_duration(Duration::from_secs_f32(0.25));
+        self.rate_limit_down
+            .set_duration(Duration::from_secs_f32(0.25));
+    }
+
+    /// Rolling, or standing still only for a moment to turn around.
+    pub fn is_moving(&self) -> bool {
+        self.speed_coeff > 0.0 || self.u_turn.is_some()
+    }
 }
 
 #[derive(Component)]
@@ -210,6 +372,8 @@ pub struct Volumes {
 pub struct GameCamera;
 
 fn main() {
+    let save_file = save::load();
+
     App::new()
         .add_systems(Startup, load_json)
         .add_plugins((
@@ -233,6 +397,13 @@ fn main() {
             JsonAssetPlugin::<structured_dialog::GameScript>::new(&[".json"]),
             splash::SplashPlugin,
             menu::MenuPlugin,
+            shop::ShopPlugin,
+            save::SavePlugin,
+            leaderboard::LeaderboardPlugin,
+            rng::RngPlugin,
+            daily::DailyPlugin,
+            tuning::TuningPlugin,
+            simulation::GameplayPlugin,
         ))
         .init_state::<AppState>()
         .insert_resource(SpeedSfx::default())
@@ -240,60 +411,27 @@ fn main() {
             volumes: vec![
                 InGameSound {
                     category: String::from("sfx"),
-                    volume: 1.,
+                    volume: save_file.settings.sfx_volume,
                 },
                 InGameSound {
                     category: String::from("music"),
-                    volume: 0.50,
+                    volume: save_file.settings.music_volume,
                 },
             ],
         })
-        .insert_resource(ResetGame(false))
-        .insert_resource(ResumeGame {
-            resume: false,
-            pause: true,
-        })
-        .insert_resource(Travel::default())
-        .insert_resource(PlayerHealth::default())
-        .insert_resource(Taxi { ..default() })
-        .insert_resource(CurrentSelection(String::new()))
-        .insert_resource(InteractionRateLimit(Timer::from_seconds(
-            0.20,
-            TimerMode::Once,
-        )))
-        .insert_resource(OccuredEvents(vec![]))
-        .insert_resource(Posessions(vec![]))
-        .insert_resource(DisplayLanguage("english"))
-        .insert_resource(structured_dialog::DialogMessage {
-            selection_index: 1,
-            ..default()
-        })
+        .insert_resource(DisplayLanguage(save_file.settings.language.clone()))
+        .insert_resource(save::SaveGame(save_file))
+        .insert_resource(structured_dialog::DialogRegistry::default())
         .insert_resource(PixelScale(1.0, 1.0))
-        .insert_resource(SpawnThingTimer {
-            timer: Timer::from_seconds(0.2, TimerMode::Repeating),
-            allow_shop: false,
-        })
         .add_systems(OnEnter(AppState::Game), (sound_controller, setup))
+        .add_systems(Update, structured_dialog::dialog_registry_system)
         .add_systems(
             PreUpdate,
             (util::window::hud_resizer, util::window::hud_scale_updater),
         )
         .add_systems(
             Update,
-            (
-                motor_sfx,
-                game_level_system,
-                road_system,
-                road_line_system,
-                car_intersection_system,
-                reset,
-                shop_spawn_system,
-                person_spawn_system,
-                movement_input_system,
-                dialog_display_system,
-                dialog_choice_selection_system,
-            )
-                .run_if(in_state(AppState::Game)),
+            (motor_sfx, dialog_display_system).run_if(in_state(AppState::Game)),
         )
         .add_systems(
             OnExit(AppState::Game),
@@ -316,6 +454,7 @@ fn setup(
     volumes: Res<Volumes>,
     mut last_dialog: ResMut<menu::LastDialog>,
     mut dialog_message: ResMut<structured_dialog::DialogMessage>,
+    tuning: Res<tuning::GameTuning>,
 ) {
     let rba_dark_gray = 0.025;
     bg.0 = Color::linear_rgba(rba_dark_gray, rba_dark_gray, rba_dark_gray, 1.0);
@@ -335,48 +474,13 @@ fn setup(
 
     if resume_game.resume {
         resume_game.pause = false;
+        // Put back as it was, without `show`, so its actions don't run again
         dialog_message.dialog = last_dialog.0.clone();
         last_dialog.0 = None;
 
         return;
     }
 
-    let player_y_start = -LANE_HEIGHT / 2.;
-    commands
-        .spawn((
-            GameState,
-            PlayerMarker,
-            Intersects::default(),
-            PlayerCar {
-                aabb: Aabb2d {
-                    min: Vec2::new(-HALF_CAR_WIDTH, player_y_start + (-53. / 2.)),
-                    max: Vec2::new(HALF_CAR_WIDTH, player_y_start + (53. / 2.)),
-                },
-                speed_coeff: 0.,
-                timer: Timer::from_seconds(0.075, TimerMode::Repeating),
-                rate_limit_up: Timer::from_seconds(0.25, TimerMode::Once),
-                rate_limit_down: Timer::from_seconds(0.25, TimerMode::Once),
-                atlas_right: (0, 2),
-                atlas_left: (3, 5),
-            },
-            Sprite {
-                flip_x: false,
-                texture_atlas: Some(TextureAtlas {
-                    layout: texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
-                        UVec2::new(89, 53),
-                        3,
-                        2,
-                        None,
-                        None,
-                    )),
-                    index: 0,
-                }),
-                image: asset_server.load("taxi-Sheet.png"),
-                ..default()
-            },
-        ))
-        .insert(Transform::from_xyz(0., -LANE_HEIGHT / 2., 0.));
-
     commands.spawn((
         GameState,
         Sprite {
@@ -399,7 +503,7 @@ fn setup(
                     ..default()
                 },
             ))
-            .insert(Transform::from_xyz(x, LANE_HEIGHT, 0.));
+            .insert(Transform::from_xyz(x, tuning.lane_height, 0.));
     }
 
     for i in -6..7 {
@@ -415,7 +519,7 @@ fn setup(
                     ..default()
                 },
             ))
-            .insert(Transform::from_xyz(x, -1. * LANE_HEIGHT, 0.));
+            .insert(Transform::from_xyz(x, -1. * tuning.lane_height, 0.));
     }
 
     commands
@@ -622,12 +726,107 @@ fn setup(
                     });
             });
 
+            p.spawn((
+                GameState,
+                Node {
+                    width: Val::Px(100.),
+                    left: Val::Px(400.),
+                    position_type: PositionType::Absolute,
+                    padding: UiRect {
+                        left: Val::Percent(1.),
+                        right: Val::Percent(1.),
+                        top: Val::Percent(1.),
+                        bottom: Val::Percent(0.),
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|p| {
+                let text_style = Node {
+                    margin: UiRect {
+                        left: Val::Px(15.),
+                        top: Val::Px(15.),
+                        right: Val::Px(15.),
+                        bottom: Val::Px(15.),
+                    },
+
+                    ..default()
+                };
+                p.spawn((GameState, text_style.clone(), Text::default()))
+                    .with_children(|p| {
+                        let text_font = TextFont {
+                            font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+                            font_size: 12.0,
+                            ..default()
+                        };
+
+                        p.spawn((
+                            GameState,
+                            UiElement(String::from("fuel")),
+                            text_font.clone(),
+                            TextSpan::new(""),
+                        ));
+                    });
+            });
+
+            // Above the seats, the running fare and what it's made of
+            p.spawn((
+                GameState,
+                Node {
+                    width: Val::Px(140.),
+                    left: Val::Px(500.),
+                    top: Val::Px(-80.),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+            ))
+            .with_children(|p| {
+                p.spawn((GameState, Text::default())).with_children(|p| {
+                    p.spawn((
+                        GameState,
+                        UiElement(String::from("meter")),
+                        TextFont {
+                            font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+                            font_size: 8.0,
+                            ..default()
+                        },
+                        TextSpan::new(""),
+                    ));
+                });
+            });
+
+            // Above the seats, pointing toward the next drop-off
+            p.spawn((
+                GameState,
+                Node {
+                    width: Val::Px(140.),
+                    left: Val::Px(500.),
+                    top: Val::Px(-20.),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+            ))
+            .with_children(|p| {
+                p.spawn((GameState, Text::default())).with_children(|p| {
+                    p.spawn((
+                        GameState,
+                        UiElement(String::from("route")),
+                        TextFont {
+                            font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+                            font_size: 12.0,
+                            ..default()
+                        },
+                        TextSpan::new(""),
+                    ));
+                });
+            });
+
             p.spawn((
                 GameState,
                 // BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
                 Node {
                     // background_color: BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
-                    width: Val::Px(100.),
+                    width: Val::Px(140.),
                     left: Val::Px(500.),
                     position_type: PositionType::Absolute,
                     // align_items: AlignItems::Start,
@@ -641,30 +840,34 @@ fn setup(
                 },
             ))
             .with_children(|p| {
-                p.spawn((
-                    GameState,
-                    PersonInCarMarker,
-                    Node {
-                        width: Val::Px(9. * 3.),
-                        height: Val::Px(22. * 3.),
-                        ..default()
-                    },
-                    BackgroundColor(Color::WHITE),
-                    ImageNode {
-                        image: asset_server.load("person-Sheet.png"),
-                        texture_atlas: Some(TextureAtlas {
-                            layout: texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
-                                UVec2::new(9, 22),
-                                28,
-                                1,
-                                None,
-                                None,
-                            )),
-                            index: 27,
-                        }),
-                        ..default()
-                    },
+                let layout = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
+                    UVec2::new(9, 22),
+                    28,
+                    1,
+                    None,
+                    None,
                 ));
+                for seat in 0..MAX_SEATS {
+                    p.spawn((
+                        GameState,
+                        PersonInCarMarker(seat),
+                        Node {
+                            width: Val::Px(9. * 3.),
+                            height: Val::Px(22. * 3.),
+                            margin: UiRect::right(Val::Px(4.)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::WHITE),
+                        ImageNode {
+                            image: asset_server.load("person-Sheet.png"),
+                            texture_atlas: Some(TextureAtlas {
+                                layout: layout.clone(),
+                                index: 27,
+                            }),
+                            ..default()
+                        },
+                    ));
+                }
             });
         });
 
@@ -715,6 +918,63 @@ fn setup(
     ));
 }
 
+/// Spawns the taxi for a new run, the rest of the scene is set up by `setup`.
+fn spawn_player(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    resume_game: Res<ResumeGame>,
+    tuning: Res<tuning::GameTuning>,
+) {
+    if resume_game.resume {
+        return;
+    }
+
+    let player_y_start = -tuning.lane_height / 2.;
+    commands
+        .spawn((
+            GameState,
+            PlayerMarker,
+            Intersects::default(),
+            PlayerCar {
+                aabb: Aabb2d {
+                    min: Vec2::new(-HALF_CAR_WIDTH, player_y_start + (-53. / 2.)),
+                    max: Vec2::new(HALF_CAR_WIDTH, player_y_start + (53. / 2.)),
+                },
+                speed_coeff: 0.,
+                timer: Timer::from_seconds(0.075, TimerMode::Repeating),
+                rate_limit_up: Timer::from_seconds(0.25, TimerMode::Once),
+                rate_limit_down: Timer::from_seconds(0.25, TimerMode::Once),
+                atlas_right: (0, 2),
+                atlas_left: (3, 5),
+                acceleration: 1.0,
+                braking: 1.0,
+                top_speed: 1.0,
+                comfort: 0.0,
+                fuel: tuning.fuel.tank,
+                damage: 0.0,
+                skid: 0.0,
+                u_turn: None,
+            },
+            Sprite {
+                flip_x: false,
+                texture_atlas: Some(TextureAtlas {
+                    layout: texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
+                        UVec2::new(89, 53),
+                        3,
+                        2,
+                        None,
+                        None,
+                    )),
+                    index: 0,
+                }),
+                image: asset_server.load("taxi-Sheet.png"),
+                ..default()
+            },
+        ))
+        .insert(Transform::from_xyz(0., player_y_start, 0.));
+}
+
 fn sound_controller(
     resume_game: Res<ResumeGame>,
     volumes: Res<Volumes>,
@@ -742,9 +1002,16 @@ fn sound_controller(
 fn car_intersection_system(
     player_car_query: Query<(&Sprite, &PlayerCar)>,
     mut npc_car_query: Query<(Entity, &mut Car)>,
+    mut crashes: EventWriter<damage::Crash>,
+    tuning: Res<tuning::GameTuning>,
 ) {
     let (player_sprite, player_car) = player_car_query.single();
     let facing_left = player_sprite.flip_x;
+    let player_velocity = if facing_left {
+        -tuning.speed_x * player_car.speed_coeff
+    } else {
+        tuning.speed_x * player_car.speed_coeff
+    };
 
     for (_, mut npc_car) in npc_car_query.iter_mut() {
         if player_car.aabb.intersects(&npc_car.aabb) {
@@ -789,27 +1056,34 @@ fn car_intersection_system(
             npc_car.intersects_player = false;
             npc_car.blocks_player_movement = false;
         }
-    }
-
-    let mut entitys_intersected: Vec<Entity> = vec![];
-    for (e1, npc_car1) in npc_car_query.iter() {
-        for (e2, npc_car2) in npc_car_query.iter() {
-            if e1 == e2 {
-                continue;
-            }
 
-            if npc_car1.aabb.intersects(&npc_car2.aabb) {
-                entitys_intersected.push(e1);
-            }
+        if npc_car.intersects_player && !npc_car.crashed {
+            npc_car.crashed = true;
+            // Cars in the top lanes drive left, cars already in a pileup stand still
+            let npc_velocity = if npc_car.intersects_npc {
+                0.
+            } else if npc_car.aabb.center().y > 0. {
+                -npc_car.speed
+            } else {
+                npc_car.speed
+            };
+            crashes.send(damage::Crash {
+                relative_speed: (player_velocity - npc_velocity).abs(),
+            });
         }
     }
 
-    npc_car_query.iter_mut().for_each(|(entity, mut npc_car)| {
-        if entitys_intersected.contains(&entity) {
-            npc_car.intersects_npc = true;
-            // info!("NPCs intersected");
+    let (entities, aabbs): (Vec<Entity>, Vec<Aabb2d>) = npc_car_query
+        .iter()
+        .map(|(entity, npc_car)| (entity, npc_car.aabb))
+        .unzip();
+    for (a, b) in broadphase::overlapping_pairs(&aabbs) {
+        for entity in [entities[a], entities[b]] {
+            if let Ok((_, mut npc_car)) = npc_car_query.get_mut(entity) {
+                npc_car.intersects_npc = true;
+            }
         }
-    });
+    }
 }
 
 #[derive(Component)]
@@ -834,9 +1108,16 @@ fn game_level_system(
     display_language: Res<DisplayLanguage>,
     mut player_data: ResMut<PlayerHealth>,
     mut dialog_message: ResMut<structured_dialog::DialogMessage>,
-    game_script_asset: Res<Assets<structured_dialog::GameScript>>,
+    dialog_engine: structured_dialog::DialogEngine,
+    mut save_game: ResMut<save::SaveGame>,
+    leaderboard: Res<leaderboard::Leaderboard>,
+    mut name_entry: ResMut<leaderboard::NameEntry>,
+    mut daily_challenge: ResMut<daily::DailyChallenge>,
+    mut daily_results: ResMut<daily::DailyResults>,
     mut ui_element_query: Query<(&UiElement, &mut TextSpan)>,
-    mut ui_person_in_car_query: Query<&mut ImageNode, With<PersonInCarMarker>>,
+    mut ui_person_in_car_query: Query<(&PersonInCarMarker, &mut ImageNode, &mut Node)>,
+    player_car_query: Query<&PlayerCar, With<PlayerMarker>>,
+    tuning: Res<tuning::GameTuning>,
 ) {
     match &dialog_message.dialog {
         Some(dialog) => match dialog.choices {
@@ -846,63 +1127,41 @@ fn game_level_system(
         None => {}
     }
 
-    let current_rider = taxi.current_rider;
-    let index = if let Some(info) = taxi.rides.iter().find(|ride| {
-        if let Some(who) = current_rider {
-            ride.who == who
-        } else {
-            false
-        }
-    }) {
-        info.passenger.sprite_index
-    } else {
-        27
-    };
-
-    for mut ui_person_in_car in ui_person_in_car_query.iter_mut() {
+    for (seat, mut ui_person_in_car, mut node) in ui_person_in_car_query.iter_mut() {
+        // Empty seats show the last frame of the sheet, seats the taxi doesn't have are hidden
+        let index = taxi
+            .riders
+            .get(seat.0)
+            .and_then(|who| taxi.rides.iter().find(|ride| ride.who == *who))
+            .map_or(27, |ride| ride.passenger.sprite_index);
         if let Some(atlas) = &mut ui_person_in_car.texture_atlas {
             atlas.index = index;
         }
+        let display = if seat.0 < tuning.pooling.seats {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if node.display != display {
+            node.display = display;
+        }
     }
 
-    let game_script = match game_script_asset.iter().next() {
-        Some(d) => d.1,
-        None => &structured_dialog::GameScript::default(),
-    };
-
     player_data.time_limit.tick(time.delta());
     for (ui_element, mut text_span) in ui_element_query.iter_mut() {
-        let must_earn = match game_script.dialogs.iter().find(|d| d.id == "must_earn") {
-            Some(dialog) => {
-                if display_language.0 == "english" {
-                    &dialog.language.english
-                } else {
-                    &dialog.language.spanish
-                }
-            }
-            None => "Must earn",
+        let must_earn = match dialog_engine.dialog("must_earn") {
+            Some(dialog) => dialog.language.text(&display_language).to_string(),
+            None => String::from("Must earn"),
         };
 
-        let earned = match game_script.dialogs.iter().find(|d| d.id == "earned") {
-            Some(dialog) => {
-                if display_language.0 == "english" {
-                    &dialog.language.english
-                } else {
-                    &dialog.language.spanish
-                }
-            }
-            None => "Earned",
+        let earned = match dialog_engine.dialog("earned") {
+            Some(dialog) => dialog.language.text(&display_language).to_string(),
+            None => String::from("Earned"),
         };
 
-        let total_earned = match game_script.dialogs.iter().find(|d| d.id == "total_earned") {
-            Some(dialog) => {
-                if display_language.0 == "english" {
-                    &dialog.language.english
-                } else {
-                    &dialog.language.spanish
-                }
-            }
-            None => "Total earned",
+        let total_earned = match dialog_engine.dialog("total_earned") {
+            Some(dialog) => dialog.language.text(&display_language).to_string(),
+            None => String::from("Total earned"),
         };
 
         if ui_element.0 == "timer" {
@@ -928,23 +1187,33 @@ fn game_level_system(
             player_data.time_limit.reset();
             player_data.earnings = 0.0;
 
-            let x = if player_data.cycles_completed < 10 {
-                (player_data.cycles_completed as f32) * 2. / 100.
-            } else if player_data.cycles_completed < 30 {
-                0.2 + (player_data.cycles_completed as f32) * 2. / 1000.
+            player_data.time_limit_required_earnings =
+                tuning.earnings_target(player_data.cycles_completed, player_data.difficulty);
+            // Daily runs can't be continued, that would be a second attempt
+            if !daily_challenge.is_active() {
+                if let Ok(player_car) = player_car_query.get_single() {
+                    save_game.checkpoint(
+                        &player_data,
+                        player_car,
+                        &dialog_engine.events,
+                        &dialog_engine.posessions,
+                    );
+                }
+            }
+        } else if taxi.riders.is_empty() {
+            if daily_challenge.is_active() {
+                daily_challenge.finish(&mut daily_results, &player_data);
+                save_game.save_profile();
+                dialog_message.show(Some(dialog_engine.game_over()));
             } else {
-                0.258
-            };
-
-            // info!("{}", 1. + x);
-            player_data.time_limit_required_earnings = (49.0_f32.powf(1. + x)).ceil();
-        } else if current_rider.is_none() {
-            let game_over = game_script
-                .dialogs
-                .iter()
-                .map(|d| d.clone())
-                .find(|d| d.id == String::from("game over"));
-            dialog_message.dialog = game_over;
+                dialog_message.show(Some(leaderboard::game_over_dialog(
+                    &dialog_engine,
+                    &leaderboard,
+                    &mut name_entry,
+                    &player_data,
+                )));
+                save_game.clear_run();
+            }
         }
     }
 }
@@ -956,6 +1225,7 @@ fn road_line_system(
     mut road_query: Query<&mut Transform, With<RoadMarker>>,
     mut roadside_query: Query<(Entity, &mut Transform, &mut RoadsideObject), Without<RoadMarker>>,
     player_query: Query<(&Sprite, &PlayerCar), With<PlayerMarker>>,
+    tuning: Res<tuning::GameTuning>,
 ) {
     if let Some(dialog) = &dialog_message.dialog {
         if dialog.choices.is_some() {
@@ -966,7 +1236,7 @@ fn road_line_system(
     let (player_sprite, player_car) = player_query.single();
     let facing_left = player_sprite.flip_x;
 
-    let player_translation_speed = SPEED_X * player_car.speed_coeff * time.delta_secs();
+    let player_translation_speed = tuning.speed_x * player_car.speed_coeff * time.delta_secs();
 
     for mut road_transform in road_query.iter_mut() {
         if road_transform.translation.x > (WINDOW_X / 2.) + 42. {
@@ -1007,6 +1277,12 @@ fn road_line_system(
 #[derive(Component)]
 pub struct ShopMarker;
 
+#[derive(Component)]
+pub struct GasStationMarker;
+
+/// Mechanic shops and gas stations, only one of them is on screen at a time.
+type RoadsideBuilding = Or<(With<ShopMarker>, With<GasStationMarker>)>;
+
 #[derive(Component)]
 pub struct RoadsideObject {
     aabb: Aabb2d,
@@ -1016,8 +1292,9 @@ fn shop_spawn_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     spawn_thing_timer: Res<SpawnThingTimer>,
+    mut game_rng: ResMut<rng::GameRng>,
     selections: Query<&SelectionMarker>,
-    shop_query: Query<(Entity, &mut Transform), With<ShopMarker>>,
+    building_query: Query<(), RoadsideBuilding>,
     roadside_object_query: Query<&RoadsideObject>,
 ) {
     if !selections.is_empty() {
@@ -1027,9 +1304,9 @@ fn shop_spawn_system(
     let x = (WINDOW_X / 2.) + 151.;
     let y = PERSON_Y_BOTTOM + 25.;
 
-    if shop_query.is_empty() {
+    if building_query.is_empty() {
         if spawn_thing_timer.timer.just_finished() && spawn_thing_timer.allow_shop {
-            if random_bool_one_in_n(5) {
+            if rng::one_in_n(&mut game_rng.shops, 5) {
                 let new_volume = Aabb2d {
                     min: Vec2::new(x - 2.25 * 96.0 / 2.0, y - 2.25 * 65.0 / 2.0),
                     max: Vec2::new(x + 2.25 * 96.0 / 2.0, y + 2.25 * 65.0 / 2.0),
@@ -1041,8 +1318,23 @@ fn shop_spawn_system(
                         || new_volume.contains(&roadside_object.aabb)
                 }) {
                     // info!(?new_volume);
-                    commands
-                        .spawn((
+                    let transform = Transform::from_xyz(x, y, 10.).with_scale(Vec3::splat(2.25));
+                    if rng::one_in_n(&mut game_rng.shops, 2) {
+                        commands.spawn((
+                            GameState,
+                            GasStationMarker,
+                            RoadsideObject { aabb: new_volume },
+                            // No gas station art yet, a tinted mechanic shop stands in
+                            Sprite {
+                                flip_x: false,
+                                color: Color::srgb(1.0, 0.6, 0.5),
+                                image: asset_server.load("mechanicshop.png"),
+                                ..default()
+                            },
+                            transform,
+                        ));
+                    } else {
+                        commands.spawn((
                             GameState,
                             ShopMarker,
                             RoadsideObject { aabb: new_volume },
@@ -1051,34 +1343,56 @@ fn shop_spawn_system(
                                 image: asset_server.load("mechanicshop.png"),
                                 ..default()
                             },
-                        ))
-                        .insert(Transform::from_xyz(x, y, 10.).with_scale(Vec3::splat(2.25)));
+                            transform,
+                        ));
+                    }
                 }
             }
         }
     }
 }
 
+/// Whether, and who, shows up at the curb, see `person_spawn_system`.
+#[derive(SystemParam)]
+pub struct PassengerSpawning<'w> {
+    spawn_thing_timer: Res<'w, SpawnThingTimer>,
+    game_rng: ResMut<'w, rng::GameRng>,
+    standing: rating::DriverStanding<'w>,
+}
+
 fn person_spawn_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    spawn_thing_timer: Res<SpawnThingTimer>,
+    spawning: PassengerSpawning,
     roadside_object_query: Query<&RoadsideObject>,
     selections: Query<&SelectionMarker>,
 ) {
+    let PassengerSpawning {
+        spawn_thing_timer,
+        mut game_rng,
+        standing,
+    } = spawning;
+
     if !selections.is_empty() {
         return;
     }
     if spawn_thing_timer.timer.just_finished() && !spawn_thing_timer.allow_shop {
-        let mut rng = rand::thread_rng();
+        let game_rng = &mut *game_rng;
+
+        // Word gets around about a poorly rated driver
+        if standing.passed_by(&mut game_rng.ratings) {
+            return;
+        }
+
+        let passengers = &mut game_rng.passengers;
 
-        let y = if random_bool_one_in_n(2) {
+        let y = if rng::one_in_n(passengers, 2) {
             PERSON_Y_TOP
         } else {
             PERSON_Y_BOTTOM
         };
-        let x = if random_bool_one_in_n(2) {
+        let x = if rng::one_in_n(passengers, 2) {
             (WINDOW_X / 2.) + 151.
         } else {
             -(WINDOW_X / 2.) - 151.
@@ -1094,8 +1408,9 @@ fn person_spawn_system(
                 || new_volume.contains(&roadside_object.aabb)
         }) {
             // info!("Person volume={:?}", new_volume);
-            let passenger_name = names::name();
-            let sprite_index = rng.gen_range(0..27);
+            let passenger_name = names::name(&mut game_rng.names);
+            let sprite_index = passengers.gen_range(0..27);
+            let personality = personality::Personality::pick(passengers);
 
             commands
                 .spawn((
@@ -1105,6 +1420,7 @@ fn person_spawn_system(
                     Passenger {
                         name: passenger_name.clone(),
                         sprite_index: sprite_index,
+                        personality,
                     },
                     Sprite {
                         flip_x: false,
@@ -1124,7 +1440,7 @@ fn person_spawn_system(
                 ))
                 .insert(Transform::from_xyz(x, y, 0.))
                 .with_children(|commands| {
-                    if random_bool_one_in_n(5) {
+                    if rng::one_in_n(passengers, 5) {
                         commands
                             .spawn((
                                 GameState,
@@ -1136,6 +1452,7 @@ fn person_spawn_system(
                             .insert(Passenger {
                                 name: passenger_name.clone(),
                                 sprite_index: sprite_index,
+                                personality,
                             })
                             .insert(PersonHighlightMarker)
                             .insert(Transform::from_xyz(0., 0., -1.))
@@ -1152,6 +1469,7 @@ fn person_spawn_system(
                             .insert(Passenger {
                                 name: passenger_name.clone(),
                                 sprite_index: sprite_index,
+                                personality,
                             })
                             .insert(PersonHighlightMarker)
                             .insert(Transform::from_xyz(0., y, -1.))
@@ -1168,11 +1486,12 @@ fn movement_input_system(
     gamepads: Query<&Gamepad>,
     mut player_query: Query<(&mut Transform, &mut Sprite, &mut PlayerCar)>,
     selections: Query<&SelectionMarker>,
+    tuning: Res<tuning::GameTuning>,
 ) {
     if !selections.is_empty() {
         return;
     }
-    let (right, left, gas, up, down) = match gamepads.iter().next() {
+    let (right, left, turn_right, turn_left, gas, up, down) = match gamepads.iter().next() {
         Some(gamepad) => {
             let left_stick_x = gamepad.get(GamepadAxis::LeftStickX).unwrap();
             let left_stick_y = gamepad.get(GamepadAxis::LeftStickY).unwrap();
@@ -1180,6 +1499,8 @@ fn movement_input_system(
             (
                 left_stick_x > 0.075,  //right
                 left_stick_x < -0.075, //left
+                left_stick_x > route::U_TURN_STICK,
+                left_stick_x < -route::U_TURN_STICK,
                 gamepad.any_pressed([
                     GamepadButton::North,
                     GamepadButton::South,
@@ -1190,11 +1511,14 @@ fn movement_input_system(
                 left_stick_y < -0.75, //down
             )
         }
-        None => (false, false, false, false, false),
+        None => (false, false, false, false, false, false, false),
     };
 
     let right = right || keyboard.any_pressed([KeyCode::KeyD, KeyCode::ArrowRight]);
     let left = left || keyboard.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]);
+    // Turning around at speed takes a fresh press, not a key that's still held
+    let turn_right = turn_right || keyboard.any_just_pressed([KeyCode::KeyD, KeyCode::ArrowRight]);
+    let turn_left = turn_left || keyboard.any_just_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]);
     let gas = gas || keyboard.pressed(KeyCode::Space);
     let up_just_pressed = up
         || keyboard.pressed(KeyCode::ArrowUp)
@@ -1205,6 +1529,23 @@ fn movement_input_system(
     let player_y = player_transform.translation.y;
     let _player_x = player_transform.translation.x;
 
+    if player_car.skid > 0.0 {
+        // Sliding on oil keeps the speed the taxi hit it at
+        player_car.skid = (player_car.skid - time.delta_secs()).max(0.0);
+        return;
+    }
+
+    if let Some(mut u_turn) = player_car.u_turn.take() {
+        u_turn.timer.tick(time.delta());
+        if u_turn.timer.finished() {
+            player_sprite.flip_x = !player_sprite.flip_x;
+            player_car.speed_coeff = u_turn.exit_speed(&tuning.u_turn);
+        } else {
+            player_car.u_turn = Some(u_turn);
+        }
+        return;
+    }
+
     if player_car.speed_coeff == 0.0 {
         if right {
             player_sprite.flip_x = false;
@@ -1212,40 +1553,58 @@ fn movement_input_system(
         if left {
             player_sprite.flip_x = true;
         }
+    } else if (turn_right && player_sprite.flip_x) || (turn_left && !player_sprite.flip_x) {
+        // Turning around at speed stops the taxi for the length of the turn
+        player_car.u_turn = Some(route::UTurn::new(player_car.speed_coeff, &tuning.u_turn));
+        player_car.speed_coeff = 0.0;
+        return;
     }
-    if gas {
-        player_car.speed_coeff = (player_car.speed_coeff + (1. * time.delta_secs())).min(1.0);
+    // An empty tank coasts to a stop, fuel::tow_system takes it from there
+    if gas && player_car.fuel > 0. {
+        player_car.speed_coeff = (player_car.speed_coeff
+            + (player_car.acceleration * time.delta_secs()))
+        .min(player_car.top_speed * tuning.damage.top_speed(player_car.damage));
     } else {
-        player_car.speed_coeff = (player_car.speed_coeff - (1. * time.delta_secs())).max(0.0);
+        player_car.speed_coeff =
+            (player_car.speed_coeff - (player_car.braking * time.delta_secs())).max(0.0);
     }
 
     player_car.rate_limit_up.tick(time.delta());
     player_car.rate_limit_down.tick(time.delta());
-    if up_just_pressed && player_y < LANE_HEIGHT {
+    let lane_height = tuning.lane_height;
+    if up_just_pressed && player_y < lane_height {
         if player_car.rate_limit_up.finished() || player_car.rate_limit_up.just_finished() {
             player_car.rate_limit_up.reset();
-            player_transform.translation.y += LANE_HEIGHT;
-            player_car.aabb.translate_by(Vec2::new(0.0, LANE_HEIGHT));
+            player_transform.translation.y += lane_height;
+            player_car.aabb.translate_by(Vec2::new(0.0, lane_height));
         }
     }
-    if down_just_pressed && player_y > -LANE_HEIGHT {
+    if down_just_pressed && player_y > -lane_height {
         if player_car.rate_limit_down.finished() || player_car.rate_limit_down.just_finished() {
             player_car.rate_limit_down.reset();
-            player_transform.translation.y -= LANE_HEIGHT;
-            player_car.aabb.translate_by(Vec2::new(0.0, -LANE_HEIGHT));
+            player_transform.translation.y -= lane_height;
+            player_car.aabb.translate_by(Vec2::new(0.0, -lane_height));
         }
     }
 }
 
+/// How fast the road scrolls and what it brings along, see `road_system`.
+#[derive(SystemParam)]
+pub struct RoadConditions<'w> {
+    time: Res<'w, Time>,
+    tuning: Res<'w, tuning::GameTuning>,
+    standing: rating::DriverStanding<'w>,
+}
+
 fn road_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    mut travel: ResMut<Travel>,
-    time: Res<Time>,
+    conditions: RoadConditions,
     mut dialog_message: ResMut<structured_dialog::DialogMessage>,
-    game_script_asset: Res<Assets<structured_dialog::GameScript>>,
+    dialog_engine: structured_dialog::DialogEngine,
     mut spawn_thing_timer: ResMut<SpawnThingTimer>,
+    mut game_rng: ResMut<rng::GameRng>,
     mut car_query: Query<
         (Entity, &mut Transform, &mut Car),
         (With<CarMarker>, Without<RoadMarker>, Without<PlayerMarker>),
@@ -1276,19 +1635,20 @@ fn road_system(
 
     mut taxi: ResMut<Taxi>,
     mut player_data: ResMut<PlayerHealth>,
+    mut ride_events: ride::RideEvents,
 ) {
-    let current_dialog_id = match &dialog_message.dialog {
-        Some(dialog) => match dialog.choices {
-            Some(_) => return,
-            None => Some(dialog.id.clone()),
-        },
-        None => None,
-    };
+    let RoadConditions {
+        time,
+        tuning,
+        standing,
+    } = conditions;
+
+    if let Some(dialog) = &dialog_message.dialog {
+        if dialog.choices.is_some() {
+            return;
+        }
+    }
 
-    let game_script = match game_script_asset.iter().next() {
-        Some(d) => d.1,
-        None => &structured_dialog::GameScript::default(),
-    };
     let (player_transform, mut player_sprite, mut player_car) = player_query.single_mut();
     player_car.timer.tick(time.delta());
     let player_y = player_transform.translation.y;
@@ -1317,11 +1677,12 @@ fn road_system(
     }
 
     let mut allow_obstable_spawn = true;
+    let mut held_up = false;
     for (obstable_entity, mut npc_car_transform, mut npc_car) in car_query.iter_mut() {
         if npc_car_transform.translation.x > (WINDOW_X / 2.) + 200. {
-            commands.entity(obstable_entity).despawn();
+            commands.entity(obstable_entity).despawn_recursive();
         } else if npc_car_transform.translation.x < -(WINDOW_X / 2.) - 200. {
-            commands.entity(obstable_entity).despawn();
+            commands.entity(obstable_entity).despawn_recursive();
         }
 
         // TODO if player_car faces the other way it should be able to "detach" and un-intersect
@@ -1331,7 +1692,8 @@ fn road_system(
         {
             // This looks like a car accident that brings that cars to a stop and on the road.
             // The player will pass these accidents at the same speed as the car moves along the road.
-            let player_translation_speed = SPEED_X * player_car.speed_coeff * time.delta_secs();
+            let player_translation_speed =
+                tuning.speed_x * player_car.speed_coeff * time.delta_secs();
             if facing_left {
                 npc_car_transform.translation.x += player_translation_speed;
                 npc_car
@@ -1391,23 +1753,17 @@ fn road_system(
         } else {
             // Speed the player_car down to a stop
             player_car.speed_coeff = (player_car.speed_coeff - (2. * time.delta_secs())).max(0.0);
+            held_up = true;
         }
 
-        if npc_car_transform.translation.x > (WINDOW_X / 2.) + 51. - 200.
-            && npc_car_transform.translation.x < (WINDOW_X / 2.) + 51. + 200.
+        // Cars come in from both edges, don't spawn one on top of another
+        if npc_car_transform.translation.x.abs() > (WINDOW_X / 2.) + 51. - 200.
+            && npc_car_transform.translation.x.abs() < (WINDOW_X / 2.) + 51. + 200.
         {
             allow_obstable_spawn = false;
         }
     }
 
-    let can_drop_off = if facing_left && player_y > 100. {
-        true
-    } else if !facing_left && player_y < -100. {
-        true
-    } else {
-        false
-    };
-
     for (_person_entity, person_global_transform, mut visibility, _) in
         person_highlight_query.iter_mut()
     {
@@ -1431,239 +1787,256 @@ fn road_system(
         };
     }
 
-    player_data.distance_traveled += SPEED_X * player_car.speed_coeff * time.delta_secs() / 1000.;
+    let distance = tuning.speed_x * player_car.speed_coeff * time.delta_secs() / 1000.;
+    player_data.distance_traveled += distance;
+    player_car.fuel =
+        (player_car.fuel - tuning.fuel.burn(distance, player_car.speed_coeff)).max(0.);
 
-    match taxi.current_rider {
-        Some(current_rider) => {
-            let info = taxi
-                .rides
-                .iter_mut()
-                .find(|ride| ride.who == current_rider)
-                .unwrap();
+    let mut paid = None;
+    for who in taxi.riders.clone() {
+        let info = taxi.rides.iter_mut().find(|ride| ride.who == who).unwrap();
 
+        if info.state.is_underway() {
             for (entity, _, _) in person_query
                 .iter_mut()
                 .filter(|(_, _, passenger)| passenger.name == info.passenger.name)
             {
                 commands.entity(entity).despawn_recursive();
             }
+        }
 
-            if !info.completed {
-                travel.traveled += SPEED_X * player_car.speed_coeff * time.delta_secs() / 1000.;
-
-                info.trip_time += time.delta_secs_f64() as f32;
-
-                if travel.traveled > travel.distance {
-                    let id = current_dialog_id.unwrap_or_default();
-                    if id == String::from("drop off soon") {
-                        // info!("Clear 1");
-                        dialog_message.dialog = None
-                    } else if player_car.speed_coeff > 0.0 {
-                        dialog_message.dialog = Some(game_script.dialogs[2].clone());
-                        info.distance_past_dropoff +=
-                            SPEED_X * player_car.speed_coeff * time.delta_secs() / 1000.;
-                        // info!("{}", info.distance_past_dropoff);
-                    } else if can_drop_off
-                        && player_car.speed_coeff == 0.0
-                        && id == String::from("here")
-                    {
-                        dialog_message.dialog = None;
-                    } else if can_drop_off
-                        && player_car.speed_coeff == 0.0
-                        && id == String::from("")
-                    {
-                        // SUCCESSFUL DROP OFF
-                        // info!("Show bye message");
-                        let distance_based_tip_adjustment = if info.distance_past_dropoff < 0.25 {
-                            3.5
-                        } else if info.distance_past_dropoff < 0.5 {
-                            0.5
-                        } else if info.distance_past_dropoff < 0.75 {
-                            -2.0
-                        } else if info.distance_past_dropoff < 1.75 {
-                            -4.0
-                        } else if info.distance_past_dropoff < 2.0 {
-                            -6.0
-                        } else if info.distance_past_dropoff < 3.0 {
-                            -8.0
-                        } else {
-                            -10.
-                        };
-
-                        // Fastest time (nearly) possible
-                        let fastest = info.distance * 1000. / SPEED_X;
-                        let time_ratio = info.trip_time / fastest;
-                        let time_past_dropoff = info.trip_time - fastest;
-                        let time_ratio_based_tip_adjustment = if time_ratio < 1.1 {
-                            4.0
-                        } else if time_ratio < 1.2 {
-                            2.5
-                        } else if time_ratio < 1.3 {
-                            0.5
-                        } else if time_ratio < 1.4 {
-                            -1.0
-                        } else if time_ratio < 1.5 {
-                            -3.0
-                        } else if time_ratio < 1.6 {
-                            -6.0
-                        } else {
-                            -10.
-                        };
-
-                        let time_past_dropoff_tip_adjustment = 1.0 - time_past_dropoff;
-
-                        //
-
-                        info.tip_percentage += distance_based_tip_adjustment
-                            + time_ratio_based_tip_adjustment
-                            + time_past_dropoff_tip_adjustment;
-
-                        // info!("{}", info.tip_percentage);
+        if info.state == ride::RideState::Arrived {
+            // SUCCESSFUL DROP OFF
+            // info!("Show bye message");
+            // The meter has the final say over the quote
+            info.trip_cost = tuning.meter_reading(info, player_data.difficulty).fare;
+            let settled = receipt::Receipt::settle(info, &tuning, player_car.damage);
+            info.tip_percentage = settled.tip_percentage;
+            info.tip = settled.tip;
+            info.receipt = Some(settled);
+
+            player_data.earnings += info.trip_cost + info.tip;
+            player_data.total_earnings += info.trip_cost + info.tip;
+            dialog_message.show(Some(dialog_engine.bye(info.passenger.personality)));
+            ride_events.completed.send(ride::RideCompleted {
+                who: info.who,
+                fare: info.trip_cost,
+                tip: info.tip,
+                stars: info.receipt.as_ref().map_or(1, |receipt| receipt.stars),
+            });
 
-                        info.tip = (info.trip_cost * (info.tip_percentage / 100.))
-                            .max(0.0)
-                            .floor();
+            let y = if player_y > 0. {
+                PERSON_Y_TOP
+            } else {
+                PERSON_Y_BOTTOM
+            };
+            commands
+                .spawn((
+                    GameState,
+                    RoadsideObject {
+                        aabb: Aabb2d {
+                            min: Vec2::splat(0.0),
+                            max: Vec2::splat(0.0),
+                        },
+                    },
+                    PersonMarker,
+                    info.passenger.clone(),
+                    Sprite {
+                        flip_x: false,
+                        texture_atlas: Some(TextureAtlas {
+                            layout: texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
+                                UVec2::new(9, 22),
+                                27,
+                                1,
+                                None,
+                                None,
+                            )),
+                            index: info.passenger.sprite_index,
+                        }),
+                        image: asset_server.load("person-Sheet.png"),
+                        ..default()
+                    },
+                ))
+                .insert(Transform::from_xyz(player_x, y, 0.));
+            info.state = ride::RideState::Paid;
+            paid = Some(who);
+        } else if info.state.is_underway() {
+            info.traveled += distance * route::heading(facing_left) * info.heading;
+            info.meter.km += distance;
+            // Stuck behind a car or swinging around, not stopped by choice
+            if held_up || player_car.u_turn.is_some() {
+                info.meter.waiting += time.delta_secs();
+            }
 
-                        player_data.earnings += info.trip_cost + info.tip;
-                        player_data.total_earnings += info.trip_cost + info.tip;
-                        dialog_message.dialog = Some(game_script.dialogs[3].clone());
+            info.trip_time += time.delta_secs_f64() as f32;
 
-                        let y = if player_y > 0. {
-                            PERSON_Y_TOP
-                        } else {
-                            PERSON_Y_BOTTOM
-                        };
-                        commands
-                            .spawn((
-                                GameState,
-                                RoadsideObject {
-                                    aabb: Aabb2d {
-                                        min: Vec2::splat(0.0),
-                                        max: Vec2::splat(0.0),
-                                    },
-                                },
-                                PersonMarker,
-                                info.passenger.clone(),
-                                Sprite {
-                                    flip_x: false,
-                                    texture_atlas: Some(TextureAtlas {
-                                        layout: texture_atlas_layouts.add(
-                                            TextureAtlasLayout::from_grid(
-                                                UVec2::new(9, 22),
-                                                27,
-                                                1,
-                                                None,
-                                                None,
-                                            ),
-                                        ),
-                                        index: info.passenger.sprite_index,
-                                    }),
-                                    image: asset_server.load("person-Sheet.png"),
-                                    ..default()
-                                },
-                            ))
-                            .insert(Transform::from_xyz(player_x, y, 0.));
-                        info.completed = true;
+            // The passenger gets out at their landmark, not just anywhere past it
+            let reach = tuning.landmarks.dropoff_reach;
+            // The landmark is on the curb to the right of the way the passenger was headed
+            let at_the_curb = if info.heading > 0. {
+                player_y < -100.
+            } else {
+                player_y > 100.
+            };
+            let state = match info.stop_ahead {
+                Some(ahead) if ahead.abs() <= reach => {
+                    if player_car.speed_coeff == 0.0 && at_the_curb {
+                        ride::RideState::Arrived
                     } else {
-                        // taxi.current_rider = None;
-                        // info!("Wait for input")
+                        info.state
+                    }
+                }
+                Some(ahead) if ahead < -reach => {
+                    if player_car.speed_coeff > 0.0 {
+                        info.distance_past_dropoff += distance;
+                    }
+                    ride::RideState::Overshot
+                }
+                // Still at the curb where the passenger got in
+                _ if info.state == ride::RideState::Accepted && player_car.speed_coeff == 0.0 => {
+                    ride::RideState::Accepted
+                }
+                // Past the stop before its landmark was put up
+                _ if info.traveled > info.distance => ride::RideState::Overshot,
+                _ if (info.traveled / info.distance) > 0.70 => ride::RideState::Approaching,
+                _ => ride::RideState::EnRoute,
+            };
+
+            if state != info.state {
+                match state {
+                    ride::RideState::Approaching => dialog_message.show(Some(
+                        dialog_engine.drop_off_soon(info.passenger.personality),
+                    )),
+                    ride::RideState::Overshot => {
+                        dialog_message.show(Some(dialog_engine.here(info.passenger.personality)))
                     }
-                } else if travel.traveled < travel.distance
-                    && (travel.traveled / travel.distance) > 0.70
-                {
-                    // info!("Drop off player soon");
-                    dialog_message.dialog = Some(game_script.dialogs[1].clone());
+                    ride::RideState::Arrived => dialog_message.show(None),
+                    _ => {}
                 }
+                info.state = state;
             }
         }
-        None => {
-            let closest_persons = person_highlight_query
-                .iter()
-                .filter(|(_, transform, visiblility, _)| {
-                    let global_transform = transform.compute_transform();
-                    global_transform.translation.x.abs() <= 50.
-                        && *visiblility == Visibility::Visible
-                })
-                .next();
-
-            match closest_persons {
-                Some((closest_rider_entity, _, _, closest_passenger)) => {
-                    if player_car.speed_coeff == 0.0 {
-                        let rider = taxi.rides.iter().find(|r| r.who == closest_rider_entity);
-                        let mut rng = rand::thread_rng();
-                        let accepted_job = match rider {
-                            Some(rider) => match rider.accepted {
-                                Some(b) => b,
-                                None => true,
-                            },
-                            None => {
-                                let d = rng.gen_range(0.25..=10.0);
-                                taxi.closest_person = Some(closest_rider_entity);
-
-                                taxi.rides.push(Ride {
-                                    who: closest_rider_entity,
-                                    passenger: closest_passenger.clone(),
-                                    accepted: None,
-                                    distance: ((d * 100.) as f32).round() / 100.,
-                                    completed: false,
-                                    trip_cost: ((d * 7.) as f32).ceil(),
-                                    tip_percentage: 10.0,
-                                    tip: 0.0,
-                                    distance_past_dropoff: 0.0,
-                                    trip_time: 0.0,
-                                });
-                                true
+    }
+    // The bye dialog shows this passenger's fare
+    if paid.is_some() {
+        taxi.closest_person = paid;
+    }
+
+    if taxi.riders.len() < tuning.pooling.seats {
+        let closest_persons = person_highlight_query
+            .iter()
+            .filter(|(_, transform, visiblility, _)| {
+                let global_transform = transform.compute_transform();
+                global_transform.translation.x.abs() <= 50. && *visiblility == Visibility::Visible
+            })
+            .next();
+
+        match closest_persons {
+            Some((closest_rider_entity, _, _, closest_passenger)) => {
+                if player_car.speed_coeff == 0.0 {
+                    let rider = taxi.rides.iter().find(|r| r.who == closest_rider_entity);
+                    let accepted_job = match rider {
+                        Some(rider) => rider.state == ride::RideState::Offered,
+                        None => {
+                            let d: f32 = game_rng.passengers.gen_range(0.25..=10.0);
+                            let destination = places::place_name(&mut game_rng.names);
+                            let vip = standing.vip(&mut game_rng.ratings);
+                            let mut trip_cost = tuning.fare(d, player_data.difficulty);
+                            if vip {
+                                trip_cost = (trip_cost * tuning.rating.vip_fares).ceil();
                             }
-                        };
-                        if accepted_job {
-                            dialog_message.dialog = Some(game_script.dialogs[0].clone());
+                            taxi.closest_person = Some(closest_rider_entity);
+
+                            let mut ride = Ride {
+                                who: closest_rider_entity,
+                                passenger: closest_passenger.clone(),
+                                state: ride::RideState::Offered,
+                                destination,
+                                heading: route::heading(facing_left),
+                                distance: (d * 100.).round() / 100.,
+                                traveled: 0.0,
+                                stop_ahead: None,
+                                trip_cost,
+                                meter: meter::FareMeter::default(),
+                                vip,
+                                pooled: false,
+                                tip_percentage: tuning.tip_percentage(player_data.difficulty)
+                                    + player_car.comfort
+                                    + tuning
+                                        .personalities
+                                        .get(closest_passenger.personality)
+                                        .tip_percentage,
+                                tip: 0.0,
+                                distance_past_dropoff: 0.0,
+                                trip_time: 0.0,
+                                receipt: None,
+                            };
+                            // Offered the shared fare when someone's already in the taxi
+                            if !taxi.riders.is_empty() {
+                                ride.pool(&tuning.pooling);
+                            }
+                            taxi.rides.push(ride);
+                            ride_events.offered.send(ride::RideOffered {
+                                who: closest_rider_entity,
+                            });
+                            true
                         }
-                        // info!("Show the dialog!");
-                    } else {
-                        dialog_message.dialog = None;
+                    };
+                    let offering = dialog_message
+                        .dialog
+                        .as_ref()
+                        .is_some_and(|dialog| dialog.id == structured_dialog::RIDE_DIALOG);
+                    if accepted_job && !offering {
+                        dialog_message
+                            .show(Some(dialog_engine.ride(closest_passenger.personality)));
                     }
+                    // info!("Show the dialog!");
+                } else if taxi.riders.is_empty() {
+                    dialog_message.show(None);
                 }
-                None => {}
             }
+            None => {}
         }
     }
 
     spawn_thing_timer.timer.tick(time.delta());
     if spawn_thing_timer.timer.just_finished() {
-        spawn_thing_timer.allow_shop = random_bool_one_in_n(4);
-        let mut rng = rand::thread_rng();
+        spawn_thing_timer.allow_shop = rng::one_in_n(&mut game_rng.shops, 4);
+        let traffic = &mut game_rng.traffic;
 
-        let y = if random_bool_one_in_n(2) {
-            LANE_HEIGHT / 2.
+        let lane_height = tuning.lane_height;
+        let y = if rng::one_in_n(traffic, 2) {
+            lane_height / 2.
         } else {
-            LANE_HEIGHT / 2. + LANE_HEIGHT
+            lane_height / 2. + lane_height
         };
-        let x = if random_bool_one_in_n(2) {
+        let x = if rng::one_in_n(traffic, 2) {
             (WINDOW_X / 2.) + 51.
         } else {
             -(WINDOW_X / 2.) - 51.
         };
-        let y = if random_bool_one_in_n(2) { -1. * y } else { y };
+        let y = if rng::one_in_n(traffic, 2) {
+            -1. * y
+        } else {
+            y
+        };
         let flip_x = if y > 0. { true } else { false };
-        let red = rng.gen_range(0.0..=1.0);
-        let green = rng.gen_range(0.0..=1.0);
-        let blue = rng.gen_range(0.0..=1.0);
-        if random_bool_one_in_n(1) && allow_obstable_spawn {
+        let red = traffic.gen_range(0.0..=1.0);
+        let green = traffic.gen_range(0.0..=1.0);
+        let blue = traffic.gen_range(0.0..=1.0);
+        if rng::one_in_n(traffic, 1) && allow_obstable_spawn {
             commands
                 .spawn((
                     GameState,
                     CarMarker,
                     Intersects::default(),
-                    Car {
-                        aabb: Aabb2d {
+                    Car::new(
+                        Aabb2d {
                             min: Vec2::new(x + (-89. / 2.), y + (-53. / 2.)),
                             max: Vec2::new(x + (89. / 2.), y + (53. / 2.)),
                         },
-                        speed: rng.gen_range(200.0..290.0),
-                        intersects_player: false,
-                        intersects_npc: false,
-                        blocks_player_movement: false,
-                    },
+                        traffic.gen_range(tuning.npc_car_speed.min..tuning.npc_car_speed.max),
+                    ),
                     Sprite {
                         flip_x: flip_x,
                         color: Color::linear_rgb(red, green, blue),
@@ -1671,14 +2044,33 @@ fn road_system(
                         ..default()
                     },
                 ))
-                .insert(Transform::from_xyz(x, y, 0.));
+                .insert(Transform::from_xyz(x, y, 0.))
+                .with_children(|parent| {
+                    parent.spawn(traffic::turn_signal());
+                });
+        }
+
+        // Oil shows up more often the longer the run goes
+        let hazards = &mut game_rng.hazards;
+        if hazards.gen::<f32>() < tuning.oil.chance(player_data.cycles_completed) {
+            let lane = hazards.gen_range(0..4) as f32;
+            let y = lane_height / 2. + lane * lane_height - 2. * lane_height;
+            let x = if facing_left {
+                -(WINDOW_X / 2.) - 51.
+            } else {
+                (WINDOW_X / 2.) + 51.
+            };
+            hazard::spawn_oil_puddle(&mut commands, &asset_server, Vec2::new(x, y));
         }
     }
 }
 
-fn random_bool_one_in_n(n: u32) -> bool {
-    let mut rng = rand::thread_rng();
-    rng.gen_range(1..=n) == 1
+/// Where the placeholders in dialog text, like `{person}` or `{coins}`, are filled in from.
+#[derive(SystemParam)]
+pub struct DialogValues<'w> {
+    taxi: Res<'w, Taxi>,
+    player_data: Res<'w, PlayerHealth>,
+    leaderboard: Res<'w, leaderboard::Leaderboard>,
 }
 
 pub fn dialog_display_system(
@@ -1687,11 +2079,13 @@ pub fn dialog_display_system(
     display_language: ResMut<DisplayLanguage>,
     dialog_message: ResMut<structured_dialog::DialogMessage>,
     dialog_display_query: Query<(Entity, &DialogDisplay), With<DialogDisplay>>,
-
-    // not consistent with regular dialog
-    taxi: Res<Taxi>,
-    player_data: Res<PlayerHealth>,
+    values: DialogValues,
 ) {
+    let DialogValues {
+        taxi,
+        player_data,
+        leaderboard,
+    } = values;
     let dialog = match &dialog_message.dialog {
         Some(d) => d,
         None => {
@@ -1703,7 +2097,7 @@ pub fn dialog_display_system(
     };
 
     for (entity, dialog_display) in dialog_display_query.iter() {
-        if dialog_display.0 != dialog.id {
+        if dialog_display.0 != dialog.display_key() {
             commands.entity(entity).despawn_recursive();
         }
     }
@@ -1715,7 +2109,7 @@ pub fn dialog_display_system(
     commands
         .spawn((
             GameState,
-            DialogDisplay(dialog.id.clone()),
+            DialogDisplay(dialog.display_key()),
             util::window::Scalers {
                 left: Some(Val::Px(20.0)),
                 // right: Some(Val::Px(75.0)),
@@ -1767,15 +2161,17 @@ pub fn dialog_display_system(
                             ..default()
                         };
 
-                        let text = if display_language.0 == "english" {
-                            &dialog.language.english
-                        } else {
-                            &dialog.language.spanish
-                        };
+                        let text = dialog.language.text(&display_language).to_string();
 
                         let text = if let Some(current_rider) = taxi.closest_person {
                             if let Some(info) = taxi.rides.iter().find(|r| r.who == current_rider) {
-                                text.replace("{person}", &info.passenger.name)
+                                let person = if info.vip {
+                                    format!("{} (VIP)", info.passenger.name)
+                                } else {
+                                    info.passenger.name.clone()
+                                };
+                                text.replace("{person}", &person)
+                                    .replace("{destination}", &info.destination)
                                     .replace("{distance}", &info.distance.to_string())
                                     .replace("{price}", &info.trip_cost.to_string())
                                     .replace("{tip}", &info.tip.to_string())
@@ -1785,14 +2181,17 @@ pub fn dialog_display_system(
                         } else {
                             text.clone()
                         };
+                        let text = text
+                            .replace("{coins}", &player_data.total_earnings.to_string())
+                            .replace("{high_scores}", &leaderboard.table());
 
-                        let text = if dialog.id == "game over" {
+                        let text = if dialog.id == structured_dialog::GAME_OVER_DIALOG {
                             let level = player_data.cycles_completed + 1;
                             let total_collected = player_data.total_earnings;
                             let rides_completed = taxi
                                 .rides
                                 .iter()
-                                .filter(|r| r.completed)
+                                .filter(|r| r.state == ride::RideState::Paid)
                                 .fold(0, |acc, _| acc + 1);
                             let total_distance =
                                 (player_data.distance_traveled * 100.).round() / 100.;
@@ -1818,11 +2217,7 @@ pub fn dialog_display_system(
                                     // let style = dialog_textbox.0.clone();
                                     let choice_id = choice.choice.clone();
 
-                                    let text = if display_language.0 == "english" {
-                                        &choice.dialog.language.english.clone()
-                                    } else {
-                                        &choice.dialog.language.spanish.clone()
-                                    };
+                                    let text = choice.dialog.language.text(&display_language);
 
                                     let text_font = TextFont {
                                         font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
@@ -1849,11 +2244,8 @@ pub fn dialog_display_system(
 pub fn reset(
     mut commands: Commands,
     mut reset_game: ResMut<ResetGame>,
-    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
-    mut travel: ResMut<Travel>,
-    mut player_data: ResMut<PlayerHealth>,
-    mut taxi: ResMut<Taxi>,
-    mut current_selection: ResMut<CurrentSelection>,
+    mut run: RunState,
+    mut player_car_query: Query<&mut PlayerCar>,
     car_query: Query<
         (Entity, &mut Transform, &mut Car),
         (With<CarMarker>, Without<RoadMarker>, Without<PlayerMarker>),
@@ -1876,11 +2268,21 @@ pub fn reset(
             commands.entity(entity).despawn_recursive();
         }
         reset_game.0 = false;
-        dialog_message.dialog = None;
-        *travel = Travel::default();
-        *player_data = PlayerHealth::default();
-        *taxi = Taxi::default();
-        current_selection.0 = String::new();
+        run.dialog_message.show(None);
+        *run.player_data = PlayerHealth::new(&run.tuning, run.player_data.difficulty);
+        *run.taxi = Taxi::default();
+        *run.reputation = patience::Reputation::default();
+        run.current_selection.0 = String::new();
+        run.occured_events.0.clear();
+        run.posessions.0.clear();
+        for mut player_car in player_car_query.iter_mut() {
+            player_car.reset_upgrades();
+            player_car.fuel = run.tuning.fuel.tank;
+            player_car.damage = 0.0;
+            player_car.skid = 0.0;
+        }
+        run.name_entry.recorded = false;
+        *run.game_rng = rng::new_run(&run.run_seed);
     }
 }
 
@@ -1893,24 +2295,44 @@ pub struct ResumeGame {
     pause: bool,
 }
 
+/// Everything picking a choice in an in-game dialog can change, see
+/// `dialog_choice_selection_system`.
+#[derive(SystemParam)]
+pub struct DialogChoiceActions<'w> {
+    display_language: Res<'w, DisplayLanguage>,
+    dialog_message: ResMut<'w, structured_dialog::DialogMessage>,
+    dialog_engine: structured_dialog::DialogEngine<'w>,
+
+    // not consistent with regular dialog
+    tuning: Res<'w, tuning::GameTuning>,
+    taxi: ResMut<'w, Taxi>,
+    reset_game: ResMut<'w, ResetGame>,
+    app_state: ResMut<'w, NextState<AppState>>,
+    resume_game: ResMut<'w, ResumeGame>,
+    ride_events: ride::RideEvents<'w>,
+}
+
 pub fn dialog_choice_selection_system(
     time: Res<Time>,
-    display_language: Res<DisplayLanguage>,
     mut interaction_rate_limit: ResMut<InteractionRateLimit>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    gamepads: Query<&Gamepad>,
+    controls: Controls,
     mut current_selection: ResMut<CurrentSelection>,
-    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
     mut selections: Query<(&SelectionMarker, &mut TextSpan)>,
-
-    // not consistent with regular dialog
-    mut travel: ResMut<Travel>,
-    mut taxi: ResMut<Taxi>,
-    mut reset_game: ResMut<ResetGame>,
-    mut app_state: ResMut<NextState<AppState>>,
-    mut resume_game: ResMut<ResumeGame>,
+    actions: DialogChoiceActions,
 ) {
-    let (_right, _left, gas, up, down, pause) = match gamepads.iter().next() {
+    let DialogChoiceActions {
+        display_language,
+        mut dialog_message,
+        mut dialog_engine,
+        tuning,
+        mut taxi,
+        mut reset_game,
+        mut app_state,
+        mut resume_game,
+        mut ride_events,
+    } = actions;
+
+    let (_right, _left, gas, up, down, pause) = match controls.gamepads.iter().next() {
         Some(gamepad) => {
             let left_stick_x = gamepad.get(GamepadAxis::LeftStickX).unwrap();
             let left_stick_y = gamepad.get(GamepadAxis::LeftStickY).unwrap();
@@ -1932,6 +2354,7 @@ pub fn dialog_choice_selection_system(
         None => (false, false, false, false, false, false),
     };
 
+    let keyboard_input = &controls.keyboard;
     let up_key_pressed = up || keyboard_input.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]);
     let down_key_pressed = down || keyboard_input.any_pressed([KeyCode::KeyS, KeyCode::ArrowDown]);
     let enter_key_just_pressed =
@@ -1967,25 +2390,61 @@ pub fn dialog_choice_selection_system(
             reset_game.0 = true;
             return;
         }
-        dialog_message.dialog = None;
-        if taxi.current_rider.is_some() {
-            taxi.current_rider = None;
-        } else if let Some(closest_person) = taxi.closest_person {
-            if current_selection.0 == "0" {
-                taxi.current_rider = taxi.closest_person;
-                match taxi.rides.iter_mut().find(|r| r.who == closest_person) {
-                    Some(ride) => {
-                        travel.distance = ride.distance;
-                        travel.traveled = 0.0;
+        if dialog.id == shop::SHOP_DIALOG
+            || dialog.id == fuel::GAS_STATION_DIALOG
+            || leaderboard::is_name_entry(dialog)
+        {
+            // Handled by shop::shop_purchase_system, fuel::fuel_purchase_system
+            // and leaderboard::name_entry_system
+            return;
+        }
+        let dialog = dialog.clone();
+        let choice = choices
+            .iter()
+            .find(|c| c.choice == current_selection.0)
+            .cloned();
+        dialog_message.show(match choice {
+            Some(choice) => dialog_engine.choose(&dialog, &choice),
+            None => None,
+        });
+
+        if dialog.id == structured_dialog::BYE_DIALOG {
+            // Everyone who paid gets out
+            let Taxi { rides, riders, .. } = &mut *taxi;
+            riders.retain(|who| {
+                rides
+                    .iter()
+                    .any(|ride| ride.who == *who && ride.state != ride::RideState::Paid)
+            });
+        } else if dialog.id == structured_dialog::RIDE_DIALOG {
+            if let Some(closest_person) = taxi.closest_person {
+                if current_selection.0 == "0" {
+                    if !taxi.riders.contains(&closest_person) {
+                        taxi.riders.push(closest_person);
+                    }
+                    // Sharing the taxi makes it a pooled ride for everyone in it
+                    let pooled = taxi.riders.len() > 1;
+                    for ride in taxi.riders_mut() {
+                        if pooled {
+                            ride.pool(&tuning.pooling);
+                        }
+                        if ride.who == closest_person {
+                            ride.traveled = 0.0;
+                            ride.state = ride::RideState::Accepted;
+                            ride_events
+                                .accepted
+                                .send(ride::RideAccepted { who: ride.who });
+                        }
                     }
-                    None => todo!(),
+                } else {
+                    taxi.rides
+                        .iter_mut()
+                        .filter(|r| r.who == closest_person)
+                        .for_each(|ride| ride.state = ride::RideState::Declined);
+                    ride_events.declined.send(ride::RideDeclined {
+                        who: closest_person,
+                    });
                 }
-            } else {
-                taxi.current_rider = None;
-                taxi.rides
-                    .iter_mut()
-                    .filter(|r| r.who == closest_person)
-                    .for_each(|ride| ride.accepted = Some(false));
             }
         }
 
@@ -2025,16 +2484,12 @@ pub fn dialog_choice_selection_system(
 
         for (choice_index, choice) in choices.iter().enumerate() {
             let choice_id = choice.choice.clone();
-            let text = if display_language.0 == "english" {
-                &choice.dialog.language.english
-            } else {
-                &choice.dialog.language.spanish
-            };
+            let text = choice.dialog.language.text(&display_language);
 
             if index == choice_index {
                 for (selection, mut text_span) in selections.iter_mut() {
                     if selection.0 == choice_id {
-                        *text_span = TextSpan::new(format!("\n\n {}", text.clone()));
+                        *text_span = TextSpan::new(format!("\n\n {}", text));
                     }
                 }
             }
@@ -2042,7 +2497,7 @@ pub fn dialog_choice_selection_system(
                 current_selection.0 = choice_id.clone();
                 for (selection, mut text_span) in selections.iter_mut() {
                     if selection.0 == choice_id {
-                        *text_span = TextSpan::new(format!("\n\n> {}", text.clone()));
+                        *text_span = TextSpan::new(format!("\n\n> {}", text));
                     }
                 }
             }
@@ -1,7 +1,12 @@
+use crate::daily;
+use crate::difficulty;
+use crate::leaderboard;
+use crate::save;
 use crate::structured_dialog;
 use crate::structured_dialog::Dialog;
 use crate::util;
 use crate::AppState;
+use crate::Controls;
 use crate::CurrentSelection;
 use crate::DialogDisplay;
 use crate::DialogTextbox;
@@ -9,7 +14,7 @@ use crate::DisplayLanguage;
 use crate::InteractionRateLimit;
 use crate::ResumeGame;
 use crate::SelectionMarker;
-use bevy::{prelude::*, render::view::RenderLayers};
+use bevy::{ecs::system::SystemParam, prelude::*, render::view::RenderLayers};
 
 #[derive(Component)]
 pub struct MenuScreen;
@@ -17,6 +22,20 @@ pub struct MenuScreen;
 #[derive(Resource, Clone, Deref, DerefMut)]
 pub struct LastDialog(pub Option<Dialog>);
 
+/// Everything picking a menu option can change, see `menu_options`.
+#[derive(SystemParam)]
+pub struct MenuActions<'w> {
+    dialog_registry: Res<'w, structured_dialog::DialogRegistry>,
+    dialog_message: ResMut<'w, structured_dialog::DialogMessage>,
+    app_state: ResMut<'w, NextState<AppState>>,
+    display_language: ResMut<'w, DisplayLanguage>,
+    volumes: ResMut<'w, crate::Volumes>,
+    resume_game: Res<'w, ResumeGame>,
+    save_game: Res<'w, save::SaveGame>,
+    continue_game: ResMut<'w, save::ContinueGame>,
+    run_mode: daily::RunMode<'w>,
+}
+
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
@@ -42,7 +61,8 @@ fn menu_setup(
     resume_game: Res<ResumeGame>,
     mut last_dialog: ResMut<LastDialog>,
     mut dialog_message: ResMut<structured_dialog::DialogMessage>,
-    game_script_asset: Res<Assets<structured_dialog::GameScript>>,
+    dialog_registry: Res<structured_dialog::DialogRegistry>,
+    save_game: Res<save::SaveGame>,
 ) {
     info!("Menu");
     bg.0 = Color::srgb(0.2, 0.2, 0.2);
@@ -54,11 +74,6 @@ fn menu_setup(
         RenderLayers::from_layers(&[2, 3]),
     ));
 
-    let game_script = match game_script_asset.iter().next() {
-        Some(d) => d.1,
-        None => &structured_dialog::GameScript::default(),
-    };
-
     let menu_id = if resume_game.resume {
         if let None = &last_dialog.0 {
             last_dialog.0 = dialog_message.dialog.clone();
@@ -68,12 +83,27 @@ fn menu_setup(
         "main menu"
     };
 
-    dialog_message.dialog = game_script
-        .dialogs
-        .iter()
-        .filter(|d| d.id == menu_id)
-        .map(|d| d.clone())
-        .next();
+    dialog_message.show(menu_dialog(&dialog_registry, menu_id, &save_game));
+}
+
+// "Continue" is only offered when there is a saved run to continue.
+fn menu_dialog(
+    dialog_registry: &structured_dialog::DialogRegistry,
+    menu_id: &str,
+    save_game: &save::SaveGame,
+) -> Option<Dialog> {
+    let mut dialog = dialog_registry.get(menu_id).cloned()?;
+    if !save_game.has_run() {
+        if let Some(choices) = &mut dialog.choices {
+            choices.retain(|c| {
+                !c.dialog
+                    .actions
+                    .events_changed_on_exit
+                    .contains(&String::from("continue_game"))
+            });
+        }
+    }
+    Some(dialog)
 }
 
 pub fn menu_system(
@@ -82,6 +112,8 @@ pub fn menu_system(
     display_language: ResMut<DisplayLanguage>,
     dialog_message: ResMut<structured_dialog::DialogMessage>,
     dialog_display_query: Query<(Entity, &DialogDisplay), With<DialogDisplay>>,
+    leaderboard: Res<leaderboard::Leaderboard>,
+    daily_results: Res<daily::DailyResults>,
 ) {
     let dialog = match &dialog_message.dialog {
         Some(d) => d,
@@ -94,7 +126,7 @@ pub fn menu_system(
     };
 
     for (entity, dialog_display) in dialog_display_query.iter() {
-        if dialog_display.0 != dialog.id {
+        if dialog_display.0 != dialog.display_key() {
             commands.entity(entity).despawn_recursive();
         }
     }
@@ -107,7 +139,7 @@ pub fn menu_system(
         .spawn((
             RenderLayers::layer(2),
             MenuScreen,
-            DialogDisplay(dialog.id.clone()),
+            DialogDisplay(dialog.display_key()),
             util::window::Scalers {
                 left: Some(Val::Px(20.0)),
                 // right: Some(Val::Px(75.0)),
@@ -165,17 +197,18 @@ pub fn menu_system(
                         ..default()
                     };
 
-                    let text = if display_language.0 == "english" {
-                        &dialog.language.english
-                    } else {
-                        &dialog.language.spanish
-                    };
+                    let text = dialog
+                        .language
+                        .text(&display_language)
+                        .replace("{high_scores}", &leaderboard.table())
+                        .replace("{date}", &daily::date(daily::today()))
+                        .replace("{daily_result}", &daily_results.summary());
 
                     p.spawn((
                         RenderLayers::layer(2),
                         MenuScreen,
                         text_font.clone(),
-                        TextSpan::new(text.clone()),
+                        TextSpan::new(text),
                     ));
                     // info!("Should be displaying: {}", text);
 
@@ -190,11 +223,7 @@ pub fn menu_system(
                                 // let style = dialog_textbox.0.clone();
                                 let choice_id = choice.choice.clone();
 
-                                let text = if display_language.0 == "english" {
-                                    &choice.dialog.language.english.clone()
-                                } else {
-                                    &choice.dialog.language.spanish.clone()
-                                };
+                                let text = choice.dialog.language.text(&display_language);
 
                                 let text_font = TextFont {
                                     font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
@@ -220,20 +249,14 @@ pub fn menu_system(
 }
 
 pub fn menu_selection_system(
-    game_script_asset: Res<Assets<structured_dialog::GameScript>>,
     time: Res<Time>,
-    display_language: ResMut<DisplayLanguage>,
     mut interaction_rate_limit: ResMut<InteractionRateLimit>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    gamepads: Query<&Gamepad>,
-    resume_game: Res<ResumeGame>,
-    volumes: ResMut<crate::Volumes>,
+    controls: Controls,
     mut current_selection: ResMut<CurrentSelection>,
-    dialog_message: ResMut<structured_dialog::DialogMessage>,
     mut selections: Query<(&SelectionMarker, &mut TextSpan)>,
-    app_state: ResMut<NextState<AppState>>,
+    menu: MenuActions,
 ) {
-    let (_right, _left, gas, up, down, _pause) = match gamepads.iter().next() {
+    let (_right, _left, gas, up, down, _pause) = match controls.gamepads.iter().next() {
         Some(gamepad) => {
             let left_stick_x = gamepad.get(GamepadAxis::LeftStickX).unwrap();
             let left_stick_y = gamepad.get(GamepadAxis::LeftStickY).unwrap();
@@ -254,6 +277,7 @@ pub fn menu_selection_system(
         }
         None => (false, false, false, false, false, false),
     };
+    let keyboard_input = &controls.keyboard;
     let up_key_pressed = up || keyboard_input.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]);
     let down_key_pressed = down || keyboard_input.any_pressed([KeyCode::KeyS, KeyCode::ArrowDown]);
     let enter_key_just_pressed =
@@ -261,19 +285,11 @@ pub fn menu_selection_system(
 
     if enter_key_just_pressed {
         debug!(?current_selection);
-        menu_options(
-            current_selection.clone(),
-            game_script_asset,
-            dialog_message,
-            app_state,
-            display_language,
-            volumes,
-            resume_game,
-        );
+        menu_options(current_selection.clone(), menu);
         return;
     }
 
-    let dialog = match &dialog_message.dialog {
+    let dialog = match &menu.dialog_message.dialog {
         Some(d) => d,
         None => {
             return;
@@ -323,16 +339,12 @@ pub fn menu_selection_system(
 
         for (choice_index, choice) in choices.iter().enumerate() {
             let choice_id = choice.choice.clone();
-            let text = if display_language.0 == "english" {
-                &choice.dialog.language.english
-            } else {
-                &choice.dialog.language.spanish
-            };
+            let text = choice.dialog.language.text(&menu.display_language);
 
             if index == choice_index {
                 for (selection, mut text_span) in selections.iter_mut() {
                     if selection.0 == choice_id {
-                        *text_span = TextSpan::new(format!("\n\n {}", text.clone()));
+                        *text_span = TextSpan::new(format!("\n\n {}", text));
                     }
                 }
             }
@@ -340,7 +352,7 @@ pub fn menu_selection_system(
                 current_selection.0 = choice_id.clone();
                 for (selection, mut text_span) in selections.iter_mut() {
                     if selection.0 == choice_id {
-                        *text_span = TextSpan::new(format!("\n\n> {}", text.clone()));
+                        *text_span = TextSpan::new(format!("\n\n> {}", text));
                     }
                 }
             }
@@ -348,15 +360,18 @@ pub fn menu_selection_system(
     }
 }
 
-fn menu_options(
-    current_selection: CurrentSelection,
-    game_script_asset: Res<Assets<structured_dialog::GameScript>>,
-    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
-    mut app_state: ResMut<NextState<AppState>>,
-    mut display_language: ResMut<DisplayLanguage>,
-    mut volumes: ResMut<crate::Volumes>,
-    resume_game: Res<ResumeGame>,
-) {
+fn menu_options(current_selection: CurrentSelection, menu: MenuActions) {
+    let MenuActions {
+        dialog_registry,
+        mut dialog_message,
+        mut app_state,
+        mut display_language,
+        mut volumes,
+        resume_game,
+        save_game,
+        mut continue_game,
+        mut run_mode,
+    } = menu;
     let dialog = match &dialog_message.dialog {
         Some(d) => d,
         None => {
@@ -375,18 +390,10 @@ fn menu_options(
     };
 
     if let Some(choice) = choices.iter().find(|c| c.choice == current_selection.0) {
-        let game_script = match game_script_asset.iter().next() {
-            Some(d) => d.1,
-            None => &structured_dialog::GameScript::default(),
-        };
         let next_id = &choice.dialog.actions.next_id;
         if !next_id.is_empty() {
-            dialog_message.dialog = game_script
-                .dialogs
-                .iter()
-                .filter(|d| d.id == next_id.clone())
-                .map(|d| d.clone())
-                .next();
+            let next = dialog_registry.get(next_id).cloned();
+            dialog_message.show(next);
         } else {
             //
             // ============ Menu Options ===============
@@ -397,7 +404,39 @@ fn menu_options(
                 .events_changed_on_exit
                 .contains(&String::from("start_game"))
             {
-                dialog_message.dialog = None;
+                if !resume_game.resume {
+                    run_mode.normal();
+                }
+                if choice
+                    .dialog
+                    .actions
+                    .events_changed_on_exit
+                    .contains(&String::from(difficulty::SET_DIFFICULTY_EVENT))
+                {
+                    if let Some(difficulty) = difficulty::Difficulty::from_id(&choice.choice) {
+                        run_mode.set_difficulty(difficulty);
+                    }
+                }
+                dialog_message.show(None);
+                app_state.set(AppState::Game);
+            } else if choice
+                .dialog
+                .actions
+                .events_changed_on_exit
+                .contains(&String::from("continue_game"))
+            {
+                continue_game.0 = true;
+                run_mode.normal();
+                dialog_message.show(None);
+                app_state.set(AppState::Game);
+            } else if choice
+                .dialog
+                .actions
+                .events_changed_on_exit
+                .contains(&String::from(daily::DAILY_CHALLENGE_EVENT))
+            {
+                run_mode.daily();
+                dialog_message.show(None);
                 app_state.set(AppState::Game);
             } else if choice
                 .dialog
@@ -405,25 +444,18 @@ fn menu_options(
                 .events_changed_on_exit
                 .contains(&String::from("show_credits"))
             {
-                dialog_message.dialog = None;
+                dialog_message.show(None);
                 app_state.set(AppState::Splash);
             } else {
                 if choice
                     .dialog
                     .actions
                     .events_changed_on_exit
-                    .contains(&String::from("english"))
-                {
-                    display_language.0 = "english";
-                }
-
-                if choice
-                    .dialog
-                    .actions
-                    .events_changed_on_exit
-                    .contains(&String::from("spanish"))
+                    .contains(&String::from(structured_dialog::SET_LOCALE_EVENT))
                 {
-                    display_language.0 = "spanish";
+                    if let Some(locale) = structured_dialog::choice_locale(choice) {
+                        display_language.0 = locale.to_string();
+                    }
                 }
 
                 if choice
@@ -488,12 +520,7 @@ fn menu_options(
                     "main menu"
                 };
 
-                dialog_message.dialog = game_script
-                    .dialogs
-                    .iter()
-                    .filter(|d| d.id == menu_id)
-                    .map(|d| d.clone())
-                    .next();
+                dialog_message.show(menu_dialog(&dialog_registry, menu_id, &save_game));
             }
         }
     }
@@ -0,0 +1,147 @@
+use crate::structured_dialog;
+use crate::tuning::GameTuning;
+use crate::AppState;
+use crate::DisplayLanguage;
+use crate::PlayerHealth;
+use crate::Taxi;
+use crate::UiElement;
+use bevy::prelude::*;
+
+pub const METER_DIALOG: &str = "meter";
+
+/// What a ride has run up so far. The passenger pays what it reads at the
+/// stop, the price in the offer is only a quote.
+#[derive(Clone, Debug, Default)]
+pub struct FareMeter {
+    /// Km driven with the passenger on board, detours included
+    pub km: f32,
+    /// Seconds held up by traffic or a U-turn with the passenger on board
+    pub waiting: f32,
+}
+
+/// The meter broken down into its charges, in coins. `fare` is what the
+/// passenger pays, rounded up and with the shared ride discount taken off.
+pub struct Reading {
+    pub flag_drop: f32,
+    pub distance: f32,
+    pub waiting: f32,
+    pub fare: f32,
+}
+
+pub struct MeterPlugin;
+
+impl Plugin for MeterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            meter_display_system
+                .after(crate::road_system)
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+/// Shows the meter of whoever's been in the taxi longest.
+fn meter_display_system(
+    taxi: Res<Taxi>,
+    tuning: Res<GameTuning>,
+    player_data: Res<PlayerHealth>,
+    display_language: Res<DisplayLanguage>,
+    dialog_engine: structured_dialog::DialogEngine,
+    mut ui_element_query: Query<(&UiElement, &mut TextSpan)>,
+) {
+    let text = match taxi.riders().find(|ride| ride.state.is_underway()) {
+        Some(ride) => {
+            let template = match dialog_engine.dialog(METER_DIALOG) {
+                Some(dialog) => dialog.language.text(&display_language).to_string(),
+                None => String::from(
+                    "Flag {flag}\nKm   {km}\nWait {wait}\nFare {fare}\n{remaining} km to go",
+                ),
+            };
+            let reading = tuning.meter_reading(ride, player_data.difficulty);
+            template
+                .replace("{flag}", &format!("{:.1}", reading.flag_drop))
+                .replace("{km}", &format!("{:.1}", reading.distance))
+                .replace("{wait}", &format!("{:.1}", reading.waiting))
+                .replace("{fare}", &reading.fare.to_string())
+                .replace(
+                    "{remaining}",
+                    &format!("{:.2}", (ride.distance - ride.traveled).abs()),
+                )
+        }
+        None => String::new(),
+    };
+
+    for (ui_element, mut text_span) in ui_element_query.iter_mut() {
+        if ui_element.0 == "meter" {
+            text_span.0 = text.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty;
+    use crate::personality::Personality;
+    use crate::ride;
+    use crate::simulation::testing::*;
+    use crate::{Car, CarMarker};
+    use bevy::math::bounding::Aabb2d;
+
+    #[test]
+    fn meter_charges_for_waiting_and_sets_the_fare() {
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+        app.world_mut()
+            .resource_mut::<GameTuning>()
+            .meter
+            .waiting_per_minute = 600.;
+        app.world_mut()
+            .spawn((UiElement(String::from("meter")), TextSpan::new("")));
+        let rider = pick_up_passenger(&mut app, Personality::Easygoing);
+        let quote = ride(&app, rider).trip_cost;
+
+        // Pulling over by choice doesn't count as waiting
+        run(&mut app, 60);
+        assert_eq!(ride(&app, rider).meter.waiting, 0.);
+
+        // Stuck behind a stopped car with the meter running
+        let y = player_y(&mut app);
+        let car = app
+            .world_mut()
+            .spawn((
+                CarMarker,
+                Car::new(
+                    Aabb2d::new(Vec2::new(60., y), Vec2::new(89. / 2., 53. / 2.)),
+                    0.,
+                ),
+                Transform::from_xyz(60., y, 0.),
+            ))
+            .id();
+        run(&mut app, 120);
+        let meter = ride(&app, rider).meter.clone();
+        assert!(meter.km == 0. && meter.waiting > 1.9);
+        let mut spans = app.world_mut().query::<(&UiElement, &TextSpan)>();
+        let (_, text) = spans
+            .iter(app.world())
+            .find(|(element, _)| element.0 == "meter")
+            .unwrap();
+        assert!(text.0.starts_with("Flag 2.0\n"), "{}", text.0);
+        assert!(text.0.contains("Wait 20."), "{}", text.0);
+
+        app.world_mut().entity_mut(car).despawn_recursive();
+        drive_to_the_stop(&mut app, rider);
+        assert_eq!(ride_state(&app, rider), ride::RideState::Paid);
+        let tuning = app.world().resource::<GameTuning>().clone();
+        let paid = ride(&app, rider);
+        assert!(paid.meter.km > 0.);
+        assert_eq!(
+            paid.trip_cost,
+            tuning
+                .meter_reading(paid, difficulty::Difficulty::Normal)
+                .fare
+        );
+        assert!(paid.trip_cost > quote + 19.);
+    }
+}
@@ -1,11 +1,11 @@
 use rand::prelude::IteratorRandom;
+use rand::Rng;
 
-pub fn name() -> String {
-    format!("{} {}", first_name(), last_name())
+pub fn name(rng: &mut impl Rng) -> String {
+    format!("{} {}", first_name(rng), last_name(rng))
 }
 
-pub fn last_name() -> String {
-    let mut rng = rand::thread_rng();
+pub fn last_name(rng: &mut impl Rng) -> String {
     r#"Aaberg
 Aalst
 Aara
@@ -21993,13 +21993,12 @@ Zwiebel
 Zysk"#
         .to_string()
         .split("\n")
-        .choose(&mut rng)
+        .choose(rng)
         .unwrap()
         .to_string()
 }
 
-pub fn first_name() -> String {
-    let mut rng = rand::thread_rng();
+pub fn first_name(rng: &mut impl Rng) -> String {
     r#"Aaren
 Aarika
 Abagael
@@ -26947,7 +26946,7 @@ Zulema
 Zuzana"#
         .to_string()
         .split("\n")
-        .choose(&mut rng)
+        .choose(rng)
         .unwrap()
         .to_string()
 }
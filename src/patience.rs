@@ -0,0 +1,239 @@
+use crate::ride;
+use crate::structured_dialog;
+use crate::tuning::GameTuning;
+use crate::AppState;
+use crate::GameState;
+use crate::Passenger;
+use crate::PersonHighlightMarker;
+use crate::PersonMarker;
+use crate::Taxi;
+use crate::WINDOW_X;
+use bevy::prelude::*;
+
+pub const REPUTATION_DIALOG: &str = "reputation";
+
+/// How long a passenger at the curb keeps waiting for the taxi. Only runs
+/// while they're on screen.
+#[derive(Component)]
+pub struct Patience(pub Timer);
+
+/// Shows over a waiting passenger once their patience runs low.
+#[derive(Component)]
+pub struct PatienceIcon;
+
+/// What drivers hear about the taxi. Turning down too many fares in a row
+/// costs tips on the rides that follow, paid rides win them back.
+#[derive(Resource, Default, Debug)]
+pub struct Reputation {
+    pub declined_in_a_row: u32,
+    /// Taken off the tip percentage of every new passenger
+    pub tip_penalty: f32,
+}
+
+pub struct PatiencePlugin;
+
+impl Plugin for PatiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Reputation>().add_systems(
+            Update,
+            (
+                (start_waiting_system, patience_system).chain(),
+                reputation_system,
+            )
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+/// Passengers hailing the taxi start waiting when they show up.
+fn start_waiting_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tuning: Res<GameTuning>,
+    person_query: Query<(Entity, &Passenger, &Children), Without<Patience>>,
+    highlight_query: Query<(), With<PersonHighlightMarker>>,
+) {
+    for (entity, passenger, children) in person_query.iter() {
+        if !children
+            .iter()
+            .any(|child| highlight_query.contains(*child))
+        {
+            continue;
+        }
+
+        let seconds =
+            tuning.patience.wait_seconds * tuning.personalities.get(passenger.personality).patience;
+        commands
+            .entity(entity)
+            .insert(Patience(Timer::from_seconds(seconds, TimerMode::Once)))
+            .with_children(|parent| {
+                parent.spawn((
+                    GameState,
+                    PatienceIcon,
+                    Sprite {
+                        image: asset_server.load("exclaimation.png"),
+                        ..default()
+                    },
+                    Transform::from_xyz(0., 30., -0.5),
+                    Visibility::Hidden,
+                ));
+            });
+    }
+}
+
+/// Waiting passengers lose patience and walk off, warning the driver first.
+fn patience_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+    dialog_message: Res<structured_dialog::DialogMessage>,
+    mut person_query: Query<(Entity, &Transform, &mut Patience, &Children), With<PersonMarker>>,
+    mut icon_query: Query<(&mut Sprite, &mut Visibility), With<PatienceIcon>>,
+) {
+    if let Some(dialog) = &dialog_message.dialog {
+        if dialog.choices.is_some() {
+            return;
+        }
+    }
+
+    for (entity, transform, mut patience, children) in person_query.iter_mut() {
+        if transform.translation.x.abs() < WINDOW_X / 2. {
+            patience.0.tick(time.delta());
+        }
+        if patience.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let left = patience.0.fraction_remaining();
+        let warn_at = tuning.patience.warn_at;
+        // Blinks once there's only half the warning time left
+        let shown =
+            left < warn_at && (left >= warn_at / 2. || (time.elapsed_secs() * 4.).fract() < 0.5);
+        let mut icons = icon_query.iter_many_mut(children);
+        while let Some((mut sprite, mut visibility)) = icons.fetch_next() {
+            *visibility = if shown {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            };
+            let calm = (left / warn_at).min(1.);
+            sprite.color = Color::srgb(1., calm, calm);
+        }
+    }
+}
+
+/// Counts fares turned down in a row and settles the reputation as rides
+/// are offered, taken, declined and paid.
+fn reputation_system(
+    tuning: Res<GameTuning>,
+    mut reputation: ResMut<Reputation>,
+    mut taxi: ResMut<Taxi>,
+    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
+    dialog_engine: structured_dialog::DialogEngine,
+    ride_events: ride::RideEventReaders,
+) {
+    let ride::RideEventReaders {
+        mut offered,
+        mut accepted,
+        mut declined,
+        mut completed,
+    } = ride_events;
+    let patience = &tuning.patience;
+    for _ in declined.read() {
+        reputation.declined_in_a_row += 1;
+        if reputation.declined_in_a_row > patience.declines_allowed {
+            reputation.tip_penalty =
+                (reputation.tip_penalty + patience.decline_penalty).min(patience.max_penalty);
+            if dialog_message.dialog.is_none() {
+                dialog_message.show(dialog_engine.dialog(REPUTATION_DIALOG));
+            }
+        }
+    }
+    if accepted.read().count() > 0 {
+        reputation.declined_in_a_row = 0;
+    }
+    for _ in completed.read() {
+        reputation.tip_penalty = (reputation.tip_penalty - patience.decline_penalty).max(0.);
+    }
+
+    for offer in offered.read() {
+        if let Some(ride) = taxi.rides.iter_mut().find(|ride| ride.who == offer.who) {
+            ride.tip_percentage -= reputation.tip_penalty;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty;
+    use crate::personality::Personality;
+    use crate::simulation::testing::*;
+    use crate::structured_dialog::GameScript;
+    use crate::CurrentSelection;
+
+    #[test]
+    fn ignored_passenger_warns_then_walks_away() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        stop_spawning(&mut app);
+        app.world_mut()
+            .resource_mut::<GameTuning>()
+            .patience
+            .wait_seconds = 1.;
+
+        // Waiting next to the taxi, which isn't pulled over to pick them up
+        spawn_passenger(&mut app, "Ada Lovelace", Personality::Easygoing);
+        run(&mut app, 2);
+        let mut icons = app
+            .world_mut()
+            .query_filtered::<&Visibility, With<PatienceIcon>>();
+        assert_eq!(*icons.single(app.world()), Visibility::Hidden);
+
+        run(&mut app, 40);
+        assert_eq!(*icons.single(app.world()), Visibility::Visible);
+
+        run(&mut app, 30);
+        let mut people = app.world_mut().query_filtered::<(), With<PersonMarker>>();
+        assert_eq!(people.iter(app.world()).count(), 0);
+    }
+
+    #[test]
+    fn turning_down_fares_in_a_row_costs_tips() {
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+        hold(&mut app, KeyCode::ArrowDown, 20);
+
+        let tuning = GameTuning::default();
+        let offer = |app: &mut App, name: &str| {
+            let mut people = app
+                .world_mut()
+                .query_filtered::<Entity, With<PersonMarker>>();
+            for person in people.iter(app.world()).collect::<Vec<_>>() {
+                app.world_mut().entity_mut(person).despawn_recursive();
+            }
+            spawn_passenger(app, name, Personality::Easygoing);
+            run(app, 30);
+            assert_eq!(
+                dialog_id(app).as_deref(),
+                Some(structured_dialog::RIDE_DIALOG)
+            );
+        };
+        for name in ["Ada Lovelace", "Grace Hopper", "Alan Turing"] {
+            offer(&mut app, name);
+            app.world_mut().resource_mut::<CurrentSelection>().0 = String::from("1");
+            hold(&mut app, KeyCode::Enter, 1);
+            run(&mut app, 1);
+        }
+        assert_eq!(dialog_id(&app).as_deref(), Some(REPUTATION_DIALOG));
+        let penalty = app.world().resource::<Reputation>().tip_penalty;
+        assert_eq!(penalty, tuning.patience.decline_penalty);
+
+        offer(&mut app, "Edsger Dijkstra");
+        let closest = app.world().resource::<Taxi>().closest_person.unwrap();
+        assert_eq!(
+            ride(&app, closest).tip_percentage,
+            tuning.tip_percentage(difficulty::Difficulty::Normal) - penalty
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::rng;
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+/// What a passenger is like. Picked when they show up at the curb, it changes
+/// how they tip and what they say during the ride.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Personality {
+    #[default]
+    Easygoing,
+    InAHurry,
+    Nervous,
+    Chatty,
+    BigTipper,
+    Cheapskate,
+}
+
+impl Personality {
+    pub const ALL: [Personality; 6] = [
+        Personality::Easygoing,
+        Personality::InAHurry,
+        Personality::Nervous,
+        Personality::Chatty,
+        Personality::BigTipper,
+        Personality::Cheapskate,
+    ];
+
+    /// Suffix of the personality's own lines in `dialog.json`, e.g. "ride in a hurry".
+    pub fn id(&self) -> &'static str {
+        match self {
+            Personality::Easygoing => "easygoing",
+            Personality::InAHurry => "in a hurry",
+            Personality::Nervous => "nervous",
+            Personality::Chatty => "chatty",
+            Personality::BigTipper => "big tipper",
+            Personality::Cheapskate => "cheapskate",
+        }
+    }
+
+    /// About half of the passengers are easygoing, the rest are split evenly.
+    pub fn pick(rng: &mut impl Rng) -> Self {
+        if rng::one_in_n(rng, 2) {
+            Personality::Easygoing
+        } else {
+            Self::ALL[rng.gen_range(1..Self::ALL.len())]
+        }
+    }
+}
+
+/// How a personality weighs the tip adjustments in `GameTuning`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PersonalityTraits {
+    /// Added to the tip percentage the passenger starts from
+    pub tip_percentage: f32,
+    /// Multiplies the `dropoff_tips` adjustment
+    pub dropoff_tips: f32,
+    /// Multiplies the trip time adjustments
+    pub trip_time_tips: f32,
+    /// Divides the trip time ratio, impatient passengers feel every slow km
+    pub patience: f32,
+}
+
+impl Default for PersonalityTraits {
+    fn default() -> Self {
+        Self {
+            tip_percentage: 0.,
+            dropoff_tips: 1.,
+            trip_time_tips: 1.,
+            patience: 1.,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Personalities {
+    pub easygoing: PersonalityTraits,
+    pub in_a_hurry: PersonalityTraits,
+    pub nervous: PersonalityTraits,
+    pub chatty: PersonalityTraits,
+    pub big_tipper: PersonalityTraits,
+    pub cheapskate: PersonalityTraits,
+}
+
+impl Default for Personalities {
+    fn default() -> Self {
+        Self {
+            easygoing: PersonalityTraits::default(),
+            in_a_hurry: PersonalityTraits {
+                trip_time_tips: 2.,
+                patience: 0.6,
+                ..default()
+            },
+            nervous: PersonalityTraits {
+                dropoff_tips: 2.,
+                trip_time_tips: 0.5,
+                ..default()
+            },
+            chatty: PersonalityTraits {
+                dropoff_tips: 0.5,
+                trip_time_tips: 0.5,
+                patience: 1.5,
+                ..default()
+            },
+            big_tipper: PersonalityTraits {
+                tip_percentage: 10.,
+                ..default()
+            },
+            cheapskate: PersonalityTraits {
+                tip_percentage: -10.,
+                dropoff_tips: 1.5,
+                ..default()
+            },
+        }
+    }
+}
+
+impl Personalities {
+    pub fn get(&self, personality: Personality) -> &PersonalityTraits {
+        match personality {
+            Personality::Easygoing => &self.easygoing,
+            Personality::InAHurry => &self.in_a_hurry,
+            Personality::Nervous => &self.nervous,
+            Personality::Chatty => &self.chatty,
+            Personality::BigTipper => &self.big_tipper,
+            Personality::Cheapskate => &self.cheapskate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::*;
+    use crate::structured_dialog;
+    use crate::tuning::GameTuning;
+
+    #[test]
+    fn big_tippers_tip_more_and_say_so() {
+        // The same ride with the same driving, only the passenger differs
+        let ride_to_the_stop = |personality| {
+            let mut app = headless_app(&shipped_script(), 7);
+            stop_spawning(&mut app);
+            let rider = pick_up_passenger(&mut app, personality);
+
+            drive_to_the_stop(&mut app, rider);
+
+            let dialog = app
+                .world()
+                .resource::<structured_dialog::DialogMessage>()
+                .dialog
+                .clone()
+                .unwrap();
+            assert_eq!(dialog.id, structured_dialog::BYE_DIALOG);
+            let text = dialog.language.text("en").to_string();
+            (ride(&app, rider).tip_percentage, text)
+        };
+
+        let (easygoing_tip, easygoing_bye) = ride_to_the_stop(Personality::Easygoing);
+        let (big_tip, big_bye) = ride_to_the_stop(Personality::BigTipper);
+        let extra = GameTuning::default()
+            .personalities
+            .get(Personality::BigTipper)
+            .tip_percentage;
+        assert!((big_tip - easygoing_tip - extra).abs() < 0.001);
+        assert!(easygoing_bye.starts_with("Thanks"));
+        assert!(big_bye.starts_with("Keep the change!"));
+    }
+}
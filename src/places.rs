@@ -0,0 +1,170 @@
+use crate::names;
+use crate::rng;
+use crate::route;
+use crate::AppState;
+use crate::GameState;
+use crate::PlayerMarker;
+use crate::RoadsideObject;
+use crate::Taxi;
+use crate::PERSON_Y_BOTTOM;
+use crate::PERSON_Y_TOP;
+use crate::WINDOW_X;
+use bevy::math::bounding::Aabb2d;
+use bevy::prelude::*;
+use rand::Rng;
+
+const KINDS: [&str; 12] = [
+    "Library", "Station", "Market", "Park", "Hospital", "Museum", "Stadium", "Plaza", "Harbor",
+    "Bakery", "Hotel", "School",
+];
+
+/// A place a passenger can ask to go, e.g. "Abbott Library" or "Ada's Bakery".
+pub fn place_name(rng: &mut impl Rng) -> String {
+    let kind = KINDS[rng.gen_range(0..KINDS.len())];
+    if rng::one_in_n(rng, 4) {
+        format!("{}'s {}", names::first_name(rng), kind)
+    } else {
+        format!("{} {}", names::last_name(rng), kind)
+    }
+}
+
+/// The building a rider is headed to. Put up by the curb once the taxi gets
+/// close, it scrolls with the road like any other `RoadsideObject`.
+#[derive(Component)]
+pub struct Landmark {
+    pub who: Entity,
+}
+
+pub struct PlacesPlugin;
+
+impl Plugin for PlacesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            landmark_system
+                .before(crate::road_system)
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+/// Puts up each rider's landmark as it comes into view and tells the ride
+/// how far ahead of the taxi it is.
+fn landmark_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut taxi: ResMut<Taxi>,
+    player_query: Query<(&Transform, &Sprite), With<PlayerMarker>>,
+    landmark_query: Query<(&Transform, &Landmark)>,
+) {
+    let Ok((player_transform, player_sprite)) = player_query.get_single() else {
+        return;
+    };
+    let player_x = player_transform.translation.x;
+    let heading = route::heading(player_sprite.flip_x);
+
+    for ride in taxi.riders_mut() {
+        ride.stop_ahead = landmark_query
+            .iter()
+            .find(|(_, landmark)| landmark.who == ride.who)
+            .map(|(transform, _)| heading * (transform.translation.x - player_x));
+        if ride.stop_ahead.is_some() || !ride.state.is_underway() {
+            continue;
+        }
+
+        // 1000 is one km, the same scale the road scrolls at
+        let ahead = (ride.distance - ride.traveled) * 1000.;
+        if ahead.abs() > WINDOW_X / 2. + 150. {
+            continue;
+        }
+        // On the curb to the right of the way the passenger is headed
+        let position = Vec2::new(
+            player_x + ride.heading * ahead,
+            if ride.heading < 0. {
+                PERSON_Y_TOP
+            } else {
+                PERSON_Y_BOTTOM
+            },
+        );
+        ride.stop_ahead = Some(heading * (position.x - player_x));
+        commands
+            .spawn((
+                GameState,
+                Landmark { who: ride.who },
+                RoadsideObject {
+                    aabb: Aabb2d::new(position, Vec2::new(1.5 * 96. / 2., 1.5 * 65. / 2.)),
+                },
+                // No landmark art yet, a tinted mechanic shop stands in
+                Sprite {
+                    color: Color::srgb(0.6, 0.8, 1.0),
+                    image: asset_server.load("mechanicshop.png"),
+                    ..default()
+                },
+                Transform::from_xyz(position.x, position.y, 1.).with_scale(Vec3::splat(1.5)),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text2d::new(ride.destination.clone()),
+                    TextFont {
+                        font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+                        font_size: 6.,
+                        ..default()
+                    },
+                    Transform::from_xyz(0., 40., 1.),
+                ));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personality::Personality;
+    use crate::ride;
+    use crate::simulation::testing::*;
+    use crate::structured_dialog::{self, GameScript};
+
+    #[test]
+    fn passengers_only_get_out_at_their_landmark() {
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+
+        let closest = hail_passenger(&mut app, Personality::Easygoing);
+        let destination = ride(&app, closest).destination.clone();
+        assert!(!destination.is_empty());
+        let dialog = app
+            .world()
+            .resource::<structured_dialog::DialogMessage>()
+            .dialog
+            .clone()
+            .unwrap();
+        assert!(dialog.language.text("en").contains("{destination}"));
+        hold(&mut app, KeyCode::Enter, 1);
+        let rider = app.world().resource::<Taxi>().riders[0];
+
+        // Sail past the landmark and stop well beyond it
+        drive_past_the_stop(&mut app, rider);
+        release(&mut app, KeyCode::Space);
+        run(&mut app, 120);
+        assert_eq!(ride_state(&app, rider), ride::RideState::Overshot);
+        assert!(ride(&app, rider).distance_past_dropoff > 0.);
+
+        let mut landmarks = app.world_mut().query::<&Landmark>();
+        assert_eq!(landmarks.single(app.world()).who, rider);
+    }
+
+    #[test]
+    fn passing_the_stop_before_its_landmark_is_up_still_overshoots() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        stop_spawning(&mut app);
+        let rider = pick_up_passenger(&mut app, Personality::Easygoing);
+
+        // Already a km past the stop, too far behind to put the landmark up
+        let mut taxi = app.world_mut().resource_mut::<Taxi>();
+        let info = taxi.rides.iter_mut().find(|r| r.who == rider).unwrap();
+        info.traveled = info.distance + 1.;
+        hold(&mut app, KeyCode::Space, 10);
+        assert_eq!(ride(&app, rider).stop_ahead, None);
+        assert_eq!(ride_state(&app, rider), ride::RideState::Overshot);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::daily;
+use crate::receipt;
+use crate::ride;
+use crate::save;
+use crate::tuning::GameTuning;
+use crate::AppState;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::Rng;
+
+/// Where the driver's average rating puts them, see `tuning::RatingTuning`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Standing {
+    /// VIPs start asking for rides, at a higher fare
+    Vip,
+    Regular,
+    /// Fewer passengers wave the taxi down
+    Poor,
+}
+
+/// The driver's standing as it applies to the current run. Daily challenges
+/// ignore it so every player gets the same passengers.
+#[derive(SystemParam)]
+pub struct DriverStanding<'w> {
+    tuning: Res<'w, GameTuning>,
+    save_game: Res<'w, save::SaveGame>,
+    daily_challenge: Res<'w, daily::DailyChallenge>,
+}
+
+impl DriverStanding<'_> {
+    pub fn get(&self) -> Standing {
+        if self.daily_challenge.is_active() {
+            return Standing::Regular;
+        }
+        self.tuning.rating.standing(&self.save_game.0.profile)
+    }
+
+    // The rolls below always draw, so the ratings stream stays in step
+    // whatever the standing.
+
+    /// Whether the next passenger walks past a poorly rated taxi.
+    pub fn passed_by(&self, rng: &mut impl Rng) -> bool {
+        let roll = rng.gen_bool(self.tuning.rating.poor_skip_chance as f64);
+        roll && self.get() == Standing::Poor
+    }
+
+    /// Whether the next passenger offered a ride is a VIP.
+    pub fn vip(&self, rng: &mut impl Rng) -> bool {
+        let roll = rng.gen_bool(self.tuning.rating.vip_chance as f64);
+        roll && self.get() == Standing::Vip
+    }
+}
+
+pub struct RatingPlugin;
+
+impl Plugin for RatingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            rating_system
+                .before(receipt::show_receipt_system)
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+/// Adds the stars from every paid ride to the driver's profile, which is
+/// saved with the next checkpoint or at game over.
+fn rating_system(
+    mut save_game: ResMut<save::SaveGame>,
+    mut completed: EventReader<ride::RideCompleted>,
+) {
+    for ride in completed.read() {
+        save_game.rate_ride(ride.stars);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty;
+    use crate::personality::Personality;
+    use crate::simulation::testing::*;
+    use crate::structured_dialog::GameScript;
+    use crate::{Passenger, PersonMarker};
+
+    fn rated_driver(app: &mut App, total_stars: u32, rated_rides: u32) {
+        app.world_mut().resource_mut::<save::SaveGame>().0.profile = save::Profile {
+            total_stars,
+            rated_rides,
+        };
+    }
+
+    #[test]
+    fn well_rated_drivers_get_vip_fares_and_every_ride_is_rated() {
+        let offer = |total_stars| {
+            let mut app = headless_app(&shipped_script(), 7);
+            stop_spawning(&mut app);
+            rated_driver(&mut app, total_stars, 10);
+            app.world_mut()
+                .resource_mut::<GameTuning>()
+                .rating
+                .vip_chance = 1.;
+            let closest = hail_passenger(&mut app, Personality::Easygoing);
+            (app, closest)
+        };
+
+        let tuning = GameTuning::default();
+        let (regular_app, regular) = offer(35);
+        assert!(!ride(&regular_app, regular).vip);
+        let (mut app, vip) = offer(48);
+        assert!(ride(&app, vip).vip);
+        assert_eq!(
+            ride(&app, vip).trip_cost,
+            (ride(&regular_app, regular).trip_cost * tuning.rating.vip_fares).ceil()
+        );
+
+        hold(&mut app, KeyCode::Enter, 1);
+        drive_to_the_stop(&mut app, vip);
+        let paid = ride(&app, vip);
+        assert_eq!(
+            paid.trip_cost,
+            tuning
+                .meter_reading(paid, difficulty::Difficulty::Normal)
+                .fare
+        );
+        let stars = paid.receipt.as_ref().unwrap().stars;
+        let profile = &app.world().resource::<save::SaveGame>().0.profile;
+        assert_eq!(profile.rated_rides, 11);
+        assert_eq!(profile.total_stars, 48 + stars);
+    }
+
+    #[test]
+    fn poorly_rated_drivers_get_fewer_passengers() {
+        let passengers_after_a_while = |total_stars| {
+            let mut app = headless_app(&GameScript::default(), 7);
+            rated_driver(&mut app, total_stars, 10);
+            app.world_mut()
+                .resource_mut::<GameTuning>()
+                .rating
+                .poor_skip_chance = 1.;
+            run(&mut app, 600);
+            app.world_mut()
+                .query_filtered::<(), With<PersonMarker>>()
+                .iter(app.world())
+                .count()
+        };
+
+        assert!(passengers_after_a_while(35) > 0);
+        assert_eq!(passengers_after_a_while(20), 0);
+    }
+
+    #[test]
+    fn the_rating_does_not_change_who_shows_up() {
+        let passengers = |total_stars| {
+            let mut app = headless_app(&GameScript::default(), 7);
+            rated_driver(&mut app, total_stars, 10);
+            app.world_mut()
+                .resource_mut::<GameTuning>()
+                .rating
+                .poor_skip_chance = 0.;
+            run(&mut app, 600);
+            let mut names: Vec<String> = app
+                .world_mut()
+                .query_filtered::<&Passenger, With<PersonMarker>>()
+                .iter(app.world())
+                .map(|p| p.name.clone())
+                .collect();
+            names.sort();
+            names
+        };
+
+        let regular = passengers(35);
+        assert!(!regular.is_empty());
+        assert_eq!(regular, passengers(20));
+    }
+
+    #[test]
+    fn daily_runs_ignore_the_rating() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        rated_driver(&mut app, 20, 10);
+        app.world_mut()
+            .resource_mut::<GameTuning>()
+            .rating
+            .poor_skip_chance = 1.;
+        app.world_mut().resource_mut::<daily::DailyChallenge>().run = Some(daily::DailyRun {
+            date: String::from("2026-10-17"),
+            official: true,
+        });
+        run(&mut app, 600);
+        let passengers = app
+            .world_mut()
+            .query_filtered::<(), With<PersonMarker>>()
+            .iter(app.world())
+            .count();
+        assert!(passengers > 0);
+    }
+}
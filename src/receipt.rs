@@ -0,0 +1,324 @@
+use crate::ride;
+use crate::save;
+use crate::structured_dialog;
+use crate::tuning::GameTuning;
+use crate::AppState;
+use crate::DisplayLanguage;
+use crate::GameState;
+use crate::Ride;
+use crate::Taxi;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+pub const RECEIPT_DIALOG: &str = "receipt";
+
+/// Why a ride's tip went up or down.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TipReason {
+    /// How far past the landmark the taxi stopped
+    Dropoff,
+    /// Trip time over the fastest possible trip time
+    TripTime,
+    /// Seconds over the fastest possible trip time
+    Waiting,
+    Damage,
+}
+
+impl TipReason {
+    /// The line's label in `dialog.json`, e.g. "receipt overshot".
+    pub fn id(&self, percentage: f32) -> &'static str {
+        match (self, percentage >= 0.) {
+            (TipReason::Dropoff, true) => "receipt on the spot",
+            (TipReason::Dropoff, false) => "receipt overshot",
+            (TipReason::TripTime, true) => "receipt quick trip",
+            (TipReason::TripTime, false) => "receipt slow trip",
+            (TipReason::Waiting, true) => "receipt no waiting",
+            (TipReason::Waiting, false) => "receipt waited too long",
+            (TipReason::Damage, _) => "receipt damage",
+        }
+    }
+
+    fn fallback(&self, percentage: f32) -> &'static str {
+        match (self, percentage >= 0.) {
+            (TipReason::Dropoff, true) => "On the spot",
+            (TipReason::Dropoff, false) => "Overshot",
+            (TipReason::TripTime, true) => "Quick trip",
+            (TipReason::TripTime, false) => "Slow trip",
+            (TipReason::Waiting, true) => "No waiting",
+            (TipReason::Waiting, false) => "Waited too long",
+            (TipReason::Damage, _) => "Dented taxi",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TipLine {
+    pub reason: TipReason,
+    /// Added to the tip percentage, already weighted by the personality
+    pub percentage: f32,
+}
+
+/// How a ride was settled, shown to the player when the passenger pays.
+#[derive(Clone, Debug)]
+pub struct Receipt {
+    pub fare: f32,
+    /// Tip percentage before the ride, from upgrades, personality and reputation
+    pub starting_tip: f32,
+    pub lines: Vec<TipLine>,
+    pub tip_percentage: f32,
+    pub tip: f32,
+    /// 1 to 5, from how the tip adjustments add up
+    pub stars: u32,
+}
+
+impl Receipt {
+    /// Works out the tip for a ride that just ended at its stop.
+    pub fn settle(ride: &Ride, tuning: &GameTuning, damage: f32) -> Self {
+        let traits = tuning.personalities.get(ride.passenger.personality);
+        // Fastest time (nearly) possible
+        let fastest = ride.distance * 1000. / tuning.speed_x;
+        let time_ratio = ride.trip_time / fastest / traits.patience;
+        let time_past_dropoff = ride.trip_time - fastest;
+
+        let lines = vec![
+            TipLine {
+                reason: TipReason::Dropoff,
+                percentage: traits.dropoff_tips
+                    * tuning.dropoff_tips.adjustment(ride.distance_past_dropoff),
+            },
+            TipLine {
+                reason: TipReason::TripTime,
+                percentage: traits.trip_time_tips * tuning.trip_time_tips.adjustment(time_ratio),
+            },
+            TipLine {
+                reason: TipReason::Waiting,
+                percentage: traits.trip_time_tips * (1.0 - time_past_dropoff),
+            },
+            TipLine {
+                reason: TipReason::Damage,
+                percentage: -tuning.damage.tip_penalty(damage),
+            },
+        ];
+        let adjustments: f32 = lines.iter().map(|line| line.percentage).sum();
+        let tip_percentage = ride.tip_percentage + adjustments;
+
+        Self {
+            fare: ride.trip_cost,
+            starting_tip: ride.tip_percentage,
+            lines,
+            tip_percentage,
+            tip: (ride.trip_cost * (tip_percentage / 100.)).max(0.0).floor(),
+            stars: tuning.receipt.stars(adjustments),
+        }
+    }
+}
+
+/// The receipt shown over the road until the bye dialog is closed.
+#[derive(Component)]
+pub struct ReceiptOverlay;
+
+pub struct ReceiptPlugin;
+
+impl Plugin for ReceiptPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (show_receipt_system, hide_receipt_system)
+                .chain()
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+/// The ride that just ended and the driver it was rated for.
+#[derive(SystemParam)]
+pub struct PaidRide<'w, 's> {
+    taxi: Res<'w, Taxi>,
+    save_game: Res<'w, save::SaveGame>,
+    completed: EventReader<'w, 's, ride::RideCompleted>,
+}
+
+pub fn show_receipt_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    paid_ride: PaidRide,
+    display_language: Res<DisplayLanguage>,
+    dialog_engine: structured_dialog::DialogEngine,
+    overlay_query: Query<Entity, With<ReceiptOverlay>>,
+) {
+    let PaidRide {
+        taxi,
+        save_game,
+        mut completed,
+    } = paid_ride;
+
+    let Some(ride) = completed
+        .read()
+        .filter_map(|event| taxi.rides.iter().find(|ride| ride.who == event.who))
+        .last()
+    else {
+        return;
+    };
+    let Some(receipt) = &ride.receipt else {
+        return;
+    };
+
+    let text = |id: &str, fallback: &str| match dialog_engine.dialog(id) {
+        Some(dialog) => dialog.language.text(&display_language).to_string(),
+        None => fallback.to_string(),
+    };
+    let lines: String = receipt
+        .lines
+        .iter()
+        .map(|line| {
+            format!(
+                "{} {:+.1}%\n",
+                text(
+                    line.reason.id(line.percentage),
+                    line.reason.fallback(line.percentage)
+                ),
+                line.percentage
+            )
+        })
+        .collect();
+    let stars = format!(
+        "{}{}",
+        "*".repeat(receipt.stars as usize),
+        "-".repeat(5 - receipt.stars as usize)
+    );
+    let text = text(
+        RECEIPT_DIALOG,
+        "RECEIPT\n\n{person}\nFare {fare}\nTip to start {start}%\n{lines}Tip {tip_percentage}% = {tip}\n\n{stars}\nDriver {rating}",
+    )
+    .replace("{person}", &ride.passenger.name)
+    .replace("{fare}", &receipt.fare.to_string())
+    .replace("{start}", &format!("{:.1}", receipt.starting_tip))
+    .replace("{lines}", &lines)
+    .replace("{tip_percentage}", &format!("{:.1}", receipt.tip_percentage))
+    .replace("{tip}", &receipt.tip.to_string())
+    .replace("{stars}", &stars)
+    .replace(
+        "{rating}",
+        &match save_game.0.profile.rating() {
+            Some(rating) => format!("{:.1}", rating),
+            None => String::from("-"),
+        },
+    );
+
+    // A shared ride can end twice in a row, the latest receipt wins
+    for overlay in overlay_query.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+    commands
+        .spawn((
+            GameState,
+            ReceiptOverlay,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(20.),
+                right: Val::Px(20.),
+                padding: UiRect::all(Val::Px(10.)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.8)),
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::new(text),
+                TextFont {
+                    font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+                    font_size: 8.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Takes the receipt down once the passenger's been waved off.
+fn hide_receipt_system(
+    mut commands: Commands,
+    dialog_message: Res<structured_dialog::DialogMessage>,
+    overlay_query: Query<Entity, With<ReceiptOverlay>>,
+) {
+    let saying_bye = dialog_message
+        .dialog
+        .as_ref()
+        .is_some_and(|dialog| dialog.id == structured_dialog::BYE_DIALOG);
+    if saying_bye {
+        return;
+    }
+    for overlay in overlay_query.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personality::Personality;
+    use crate::simulation::testing::*;
+    use crate::tuning::{ReceiptTuning, TipTable};
+
+    #[test]
+    fn receipt_breaks_down_the_tip_until_the_passenger_leaves() {
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+        // However the drive goes, the trip time costs the same part of the tip
+        app.world_mut().resource_mut::<GameTuning>().trip_time_tips = TipTable {
+            steps: vec![],
+            otherwise: -4.,
+        };
+        let rider = pick_up_passenger(&mut app, Personality::Easygoing);
+        drive_to_the_stop(&mut app, rider);
+
+        let paid = ride(&app, rider);
+        let receipt = paid.receipt.clone().unwrap();
+        let adjustments: f32 = receipt.lines.iter().map(|line| line.percentage).sum();
+        assert!((receipt.starting_tip + adjustments - paid.tip_percentage).abs() < 0.001);
+        assert_eq!(receipt.tip, paid.tip);
+        assert_eq!(receipt.fare, paid.trip_cost);
+        let tuning = GameTuning::default();
+        assert_eq!(receipt.stars, tuning.receipt.stars(adjustments));
+        let slow_trip = -4.
+            * tuning
+                .personalities
+                .get(Personality::Easygoing)
+                .trip_time_tips;
+        let trip_time = receipt
+            .lines
+            .iter()
+            .find(|line| line.reason == TipReason::TripTime)
+            .unwrap();
+        assert_eq!(trip_time.percentage, slow_trip);
+
+        let mut overlay = app
+            .world_mut()
+            .query_filtered::<&Children, With<ReceiptOverlay>>();
+        let text = overlay.single(app.world())[0];
+        let text = &app.world().get::<Text>(text).unwrap().0;
+        assert!(text.starts_with("RECEIPT\n\nAda Lovelace\n"), "{}", text);
+        assert!(
+            text.contains(&format!("\nSlow trip {:+.1}%\n", slow_trip)),
+            "{}",
+            text
+        );
+        assert!(text.contains(&format!("= {}", receipt.tip)));
+
+        hold(&mut app, KeyCode::Enter, 1);
+        run(&mut app, 1);
+        assert!(overlay.iter(app.world()).next().is_none());
+    }
+
+    #[test]
+    fn each_threshold_reached_earns_a_star_up_to_five() {
+        let receipt = ReceiptTuning {
+            star_thresholds: vec![-10., -5., 0., 5., 10.],
+        };
+        assert_eq!(receipt.stars(-20.), 1);
+        assert_eq!(receipt.stars(-10.), 2);
+        assert_eq!(receipt.stars(-7.5), 2);
+        assert_eq!(receipt.stars(0.), 4);
+        assert_eq!(receipt.stars(5.), 5);
+        assert_eq!(receipt.stars(20.), 5);
+    }
+}
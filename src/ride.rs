@@ -0,0 +1,88 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+/// Where a ride is at, from the passenger hailing the taxi to paying.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RideState {
+    /// The passenger asked for a ride and is waiting on an answer
+    #[default]
+    Offered,
+    Accepted,
+    EnRoute,
+    /// Most of the distance is covered, the passenger says their stop is coming up
+    Approaching,
+    /// Still driving after passing the stop
+    Overshot,
+    /// Stopped within `dropoff_reach` of the destination's `Landmark`, the fare
+    /// is paid next
+    Arrived,
+    Paid,
+    Declined,
+}
+
+impl RideState {
+    /// The passenger is in the taxi and the fare meter is running.
+    pub fn is_underway(&self) -> bool {
+        matches!(
+            self,
+            RideState::Accepted
+                | RideState::EnRoute
+                | RideState::Approaching
+                | RideState::Overshot
+                | RideState::Arrived
+        )
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct RideOffered {
+    pub who: Entity,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct RideAccepted {
+    pub who: Entity,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct RideDeclined {
+    pub who: Entity,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct RideCompleted {
+    pub who: Entity,
+    pub fare: f32,
+    pub tip: f32,
+    /// 1 to 5, from the receipt
+    pub stars: u32,
+}
+
+/// Writers for the ride events.
+#[derive(SystemParam)]
+pub struct RideEvents<'w> {
+    pub offered: EventWriter<'w, RideOffered>,
+    pub accepted: EventWriter<'w, RideAccepted>,
+    pub declined: EventWriter<'w, RideDeclined>,
+    pub completed: EventWriter<'w, RideCompleted>,
+}
+
+/// Readers for the ride events, the counterpart to `RideEvents`.
+#[derive(SystemParam)]
+pub struct RideEventReaders<'w, 's> {
+    pub offered: EventReader<'w, 's, RideOffered>,
+    pub accepted: EventReader<'w, 's, RideAccepted>,
+    pub declined: EventReader<'w, 's, RideDeclined>,
+    pub completed: EventReader<'w, 's, RideCompleted>,
+}
+
+pub struct RidePlugin;
+
+impl Plugin for RidePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RideOffered>()
+            .add_event::<RideAccepted>()
+            .add_event::<RideDeclined>()
+            .add_event::<RideCompleted>();
+    }
+}
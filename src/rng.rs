@@ -0,0 +1,140 @@
+use crate::AppState;
+use crate::ResumeGame;
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Every random draw in a run comes from here so a run can be replayed from
+/// its seed. Each subsystem has its own stream, so e.g. spawning an extra car
+/// doesn't change which passengers show up.
+#[derive(Resource)]
+pub struct GameRng {
+    pub traffic: ChaCha8Rng,
+    pub passengers: ChaCha8Rng,
+    pub names: ChaCha8Rng,
+    pub shops: ChaCha8Rng,
+    pub hazards: ChaCha8Rng,
+    /// Rolls that depend on the driver's rating, see `rating::DriverStanding`
+    pub ratings: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        let stream = |n: u64| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(n);
+            rng
+        };
+        Self {
+            traffic: stream(1),
+            passengers: stream(2),
+            names: stream(3),
+            shops: stream(4),
+            hazards: stream(5),
+            ratings: stream(6),
+        }
+    }
+}
+
+/// The seed the next run starts from. Defaults to `--seed <n>` from the
+/// command line (or `?seed=<n>` on the web), otherwise every run gets a fresh one.
+#[derive(Resource)]
+pub struct RunSeed(pub Option<u64>);
+
+impl Default for RunSeed {
+    fn default() -> Self {
+        Self(requested_seed())
+    }
+}
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        let run_seed = RunSeed::default();
+        app.insert_resource(new_run(&run_seed))
+            .insert_resource(run_seed)
+            .add_systems(OnEnter(AppState::Game), start_run_system);
+    }
+}
+
+fn start_run_system(mut commands: Commands, run_seed: Res<RunSeed>, resume_game: Res<ResumeGame>) {
+    // Coming back from the pause menu carries on with the same run
+    if resume_game.resume {
+        return;
+    }
+    commands.insert_resource(new_run(&run_seed));
+}
+
+/// Reseeds for a new run, logging the seed so a run can be reported and replayed.
+pub fn new_run(run_seed: &RunSeed) -> GameRng {
+    let seed = run_seed.0.unwrap_or_else(|| rand::thread_rng().gen());
+    info!("Starting run with seed {}", seed);
+    GameRng::from_seed(seed)
+}
+
+pub fn one_in_n(rng: &mut impl Rng, n: u32) -> bool {
+    rng.gen_range(1..=n) == 1
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn requested_seed() -> Option<u64> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+    parse_seed(&args.next()?)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn requested_seed() -> Option<u64> {
+    let search = web_sys::window()?.location().search().ok()?;
+    let value = search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("seed="))?;
+    parse_seed(value)
+}
+
+fn parse_seed(value: &str) -> Option<u64> {
+    match value.parse() {
+        Ok(seed) => Some(seed),
+        Err(e) => {
+            warn!("Ignoring seed {:?}: {}", value, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    // Enters the game with a run seeded 7 already going and returns the
+    // next passenger draw
+    fn enter_game(resume: bool) -> u64 {
+        let mut world = World::new();
+        let mut game_rng = GameRng::from_seed(7);
+        game_rng.passengers.gen::<u64>();
+        world.insert_resource(game_rng);
+        world.insert_resource(RunSeed(Some(42)));
+        world.insert_resource(ResumeGame {
+            resume,
+            pause: resume,
+        });
+
+        world.run_system_once(start_run_system).unwrap();
+        world.resource_mut::<GameRng>().passengers.gen()
+    }
+
+    #[test]
+    fn resuming_from_pause_keeps_the_run_going() {
+        let mut same_run = GameRng::from_seed(7);
+        same_run.passengers.gen::<u64>();
+        assert_eq!(enter_game(true), same_run.passengers.gen::<u64>());
+    }
+
+    #[test]
+    fn a_new_game_starts_from_the_run_seed() {
+        let mut new_run = GameRng::from_seed(42);
+        assert_eq!(enter_game(false), new_run.passengers.gen::<u64>());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::tuning::UTurnTuning;
+use crate::AppState;
+use crate::Taxi;
+use crate::UiElement;
+use bevy::prelude::*;
+
+/// How far the stick has to be pushed the other way to turn around at speed,
+/// well past the steering deadzone so a drifting stick doesn't.
+pub const U_TURN_STICK: f32 = 0.75;
+
+/// Swinging the taxi around while it's moving. It stands still until the
+/// timer runs out, then drives off the other way a little slower.
+pub struct UTurn {
+    pub timer: Timer,
+    /// Speed the taxi was going when it started turning
+    pub speed: f32,
+}
+
+impl UTurn {
+    pub fn new(speed: f32, tuning: &UTurnTuning) -> Self {
+        Self {
+            timer: Timer::from_seconds(tuning.seconds, TimerMode::Once),
+            speed,
+        }
+    }
+
+    /// Speed the taxi comes out of the turn with.
+    pub fn exit_speed(&self, tuning: &UTurnTuning) -> f32 {
+        self.speed * tuning.speed_kept
+    }
+}
+
+/// 1 while the taxi faces right, -1 while it faces left.
+pub fn heading(facing_left: bool) -> f32 {
+    if facing_left {
+        -1.
+    } else {
+        1.
+    }
+}
+
+pub struct RoutePlugin;
+
+impl Plugin for RoutePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, route_arrow_system.run_if(in_state(AppState::Game)));
+    }
+}
+
+/// Points the HUD toward the closest drop-off and shows how far off it is.
+fn route_arrow_system(taxi: Res<Taxi>, mut ui_element_query: Query<(&UiElement, &mut TextSpan)>) {
+    let closest = taxi
+        .riders()
+        .filter(|ride| ride.state.is_underway())
+        .map(|ride| ride.heading * (ride.distance - ride.traveled))
+        .min_by(|a, b| a.abs().total_cmp(&b.abs()));
+
+    for (ui_element, mut text_span) in ui_element_query.iter_mut() {
+        if ui_element.0 == "route" {
+            text_span.0 = match closest {
+                Some(km) if km < 0. => format!("< {:.2} km", -km),
+                Some(km) => format!("{:.2} km >", km),
+                None => String::new(),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personality::Personality;
+    use crate::ride;
+    use crate::simulation::testing::*;
+    use crate::structured_dialog::GameScript;
+    use crate::tuning::GameTuning;
+    use crate::PlayerCar;
+
+    #[test]
+    fn u_turn_at_speed_heads_back_to_a_missed_stop() {
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+        let rider = pick_up_passenger(&mut app, Personality::Easygoing);
+
+        let tuning = GameTuning::default();
+        drive_past_the_stop(&mut app, rider);
+
+        // Turning around stops the taxi for a moment, then it heads back
+        hold(&mut app, KeyCode::ArrowLeft, 1);
+        let mut car = app.world_mut().query::<(&PlayerCar, &Sprite)>();
+        let (player_car, sprite) = car.single(app.world());
+        assert!(player_car.u_turn.is_some() && player_car.speed_coeff == 0.);
+        assert!(!sprite.flip_x);
+        let traveled = ride(&app, rider).traveled;
+        run(&mut app, (tuning.u_turn.seconds * 60.) as u32 + 1);
+        let (player_car, sprite) = car.single(app.world());
+        assert!(player_car.u_turn.is_none() && player_car.speed_coeff > 0.);
+        assert!(sprite.flip_x);
+        run(&mut app, 1);
+        assert!(ride(&app, rider).stop_ahead.unwrap() > 0.);
+
+        release(&mut app, KeyCode::Space);
+        drive_to_the_stop(&mut app, rider);
+        assert!(ride(&app, rider).traveled < traveled);
+        assert_eq!(ride_state(&app, rider), ride::RideState::Paid);
+        assert!(ride(&app, rider).distance_past_dropoff > 0.);
+    }
+
+    #[test]
+    fn holding_the_other_way_does_not_turn_around() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        stop_spawning(&mut app);
+        hold(&mut app, KeyCode::Space, 30);
+
+        // Still held from before, only a fresh press swings the taxi around
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.press(KeyCode::ArrowLeft);
+        keyboard.clear_just_pressed(KeyCode::ArrowLeft);
+        run(&mut app, 10);
+        let mut car = app.world_mut().query::<(&PlayerCar, &Sprite)>();
+        let (player_car, sprite) = car.single(app.world());
+        assert!(player_car.u_turn.is_none() && player_car.speed_coeff > 0.);
+        assert!(!sprite.flip_x);
+    }
+}
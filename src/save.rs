@@ -0,0 +1,539 @@
+use crate::difficulty;
+use crate::shop;
+use crate::structured_dialog;
+use crate::tuning;
+use crate::AppState;
+use crate::DisplayLanguage;
+use crate::OccuredEvents;
+use crate::PlayerCar;
+use crate::PlayerHealth;
+use crate::PlayerMarker;
+use crate::Posessions;
+use crate::SpawnThingTimer;
+use crate::Volumes;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Bump this when `SaveFile` changes shape and add a step to `MIGRATIONS`.
+pub const SAVE_VERSION: u32 = 4;
+const SAVE_FILE: &str = "save.json";
+/// Where a save that could not be loaded is kept before it gets overwritten.
+const BACKUP_FILE: &str = "save.backup.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SaveFile {
+    pub version: u32,
+    pub settings: Settings,
+    /// The run to pick up with "Continue", cleared on game over.
+    pub run: Option<RunProgress>,
+    pub profile: Profile,
+}
+
+impl Default for SaveFile {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            settings: Settings::default(),
+            run: None,
+            profile: Profile::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Settings {
+    pub language: String,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: String::from(structured_dialog::DEFAULT_LOCALE),
+            music_volume: 0.50,
+            sfx_volume: 1.,
+        }
+    }
+}
+
+/// The driver's record, kept across runs.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Profile {
+    /// Stars from every paid ride, 1 to 5 each
+    pub total_stars: u32,
+    pub rated_rides: u32,
+}
+
+impl Profile {
+    /// Average stars per ride, none until the first paid ride.
+    pub fn rating(&self) -> Option<f32> {
+        if self.rated_rides == 0 {
+            return None;
+        }
+        Some(self.total_stars as f32 / self.rated_rides as f32)
+    }
+}
+
+/// Progress as of the start of the current round.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RunProgress {
+    pub cycles_completed: u32,
+    pub time_limit_required_earnings: f32,
+    pub total_earnings: f32,
+    pub spent: f32,
+    pub distance_traveled: f32,
+    pub difficulty: difficulty::Difficulty,
+    pub events: Vec<String>,
+    pub posessions: Vec<String>,
+    /// Litres left in the taxi's tank
+    pub fuel: f32,
+    pub damage: f32,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct SaveGame(pub SaveFile);
+
+impl SaveGame {
+    pub fn has_run(&self) -> bool {
+        self.0.run.is_some()
+    }
+
+    pub fn checkpoint(
+        &mut self,
+        player_data: &PlayerHealth,
+        player_car: &PlayerCar,
+        events: &OccuredEvents,
+        posessions: &Posessions,
+    ) {
+        self.0.run = Some(RunProgress {
+            cycles_completed: player_data.cycles_completed,
+            time_limit_required_earnings: player_data.time_limit_required_earnings,
+            total_earnings: player_data.total_earnings,
+            spent: player_data.spent,
+            distance_traveled: player_data.distance_traveled,
+            difficulty: player_data.difficulty,
+            events: events.0.clone(),
+            posessions: posessions.0.clone(),
+            fuel: player_car.fuel,
+            damage: player_car.damage,
+        });
+        write(&self.0);
+    }
+
+    /// Only kept in memory until the next checkpoint or game over writes it out.
+    pub fn rate_ride(&mut self, stars: u32) {
+        self.0.profile.total_stars += stars;
+        self.0.profile.rated_rides += 1;
+    }
+
+    /// Ends the run, writing out the profile it was rated into.
+    pub fn clear_run(&mut self) {
+        self.0.run = None;
+        write(&self.0);
+    }
+
+    /// Writes out the profile after a run that leaves the saved run alone.
+    pub fn save_profile(&self) {
+        write(&self.0);
+    }
+}
+
+/// Set from the main menu to restore the saved run once the game starts.
+#[derive(Resource)]
+pub struct ContinueGame(pub bool);
+
+/// Upgrades bought during a continued run, replayed onto the car once the
+/// shop catalogue has loaded.
+#[derive(Resource, Default)]
+struct PendingUpgrades(Option<Vec<String>>);
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ContinueGame(false))
+            .init_resource::<PendingUpgrades>()
+            .add_systems(Update, save_settings_system)
+            .add_systems(
+                Update,
+                (continue_game_system, replay_upgrades_system)
+                    .chain()
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+fn save_settings_system(
+    mut save_game: ResMut<SaveGame>,
+    display_language: Res<DisplayLanguage>,
+    volumes: Res<Volumes>,
+) {
+    if display_language.is_added() || !(display_language.is_changed() || volumes.is_changed()) {
+        return;
+    }
+
+    for volume in volumes.volumes.iter() {
+        match volume.category.as_str() {
+            "music" => save_game.0.settings.music_volume = volume.volume,
+            "sfx" => save_game.0.settings.sfx_volume = volume.volume,
+            _ => {}
+        }
+    }
+    save_game.0.settings.language = display_language.0.clone();
+    write(&save_game.0);
+}
+
+/// The run state a saved run is restored into by `continue_game_system`.
+#[derive(SystemParam)]
+struct RestoredRun<'w, 's> {
+    player_data: ResMut<'w, PlayerHealth>,
+    occured_events: ResMut<'w, OccuredEvents>,
+    posessions: ResMut<'w, Posessions>,
+    spawn_thing_timer: ResMut<'w, SpawnThingTimer>,
+    player_car_query: Query<'w, 's, &'static mut PlayerCar, With<PlayerMarker>>,
+}
+
+fn continue_game_system(
+    mut continue_game: ResMut<ContinueGame>,
+    save_game: Res<SaveGame>,
+    mut pending_upgrades: ResMut<PendingUpgrades>,
+    restored: RestoredRun,
+    tuning: Res<tuning::GameTuning>,
+) {
+    let RestoredRun {
+        mut player_data,
+        mut occured_events,
+        mut posessions,
+        mut spawn_thing_timer,
+        mut player_car_query,
+    } = restored;
+
+    if !continue_game.0 {
+        return;
+    }
+    continue_game.0 = false;
+
+    let Some(run) = &save_game.0.run else {
+        return;
+    };
+
+    *player_data = PlayerHealth {
+        cycles_completed: run.cycles_completed,
+        time_limit_required_earnings: run.time_limit_required_earnings,
+        total_earnings: run.total_earnings,
+        spent: run.spent,
+        distance_traveled: run.distance_traveled,
+        ..PlayerHealth::new(&tuning, run.difficulty)
+    };
+    spawn_thing_timer
+        .timer
+        .set_duration(tuning.spawn_interval(run.difficulty));
+    occured_events.0 = run.events.clone();
+    posessions.0 = run.posessions.clone();
+    for mut player_car in player_car_query.iter_mut() {
+        player_car.fuel = run.fuel.min(tuning.fuel.tank);
+        player_car.damage = run.damage;
+    }
+
+    // Upgrades live on the car, so replay the ones bought during the run
+    pending_upgrades.0 = Some(run.posessions.clone());
+}
+
+fn replay_upgrades_system(
+    mut pending_upgrades: ResMut<PendingUpgrades>,
+    catalogue: shop::Catalogue,
+    mut player_car_query: Query<&mut PlayerCar>,
+) {
+    let Some(posessions) = &pending_upgrades.0 else {
+        return;
+    };
+    // shop.json may still be loading when a run is continued from the menu
+    let Some(catalogue) = catalogue.get() else {
+        return;
+    };
+
+    for mut player_car in player_car_query.iter_mut() {
+        player_car.reset_upgrades();
+        for upgrade in catalogue
+            .upgrades
+            .iter()
+            .filter(|u| posessions.contains(&u.id))
+        {
+            shop::apply_upgrade(&mut player_car, upgrade);
+        }
+    }
+    pending_upgrades.0 = None;
+}
+
+pub fn load() -> SaveFile {
+    let Some(contents) = read(SAVE_FILE) else {
+        return SaveFile::default();
+    };
+
+    let save_file = serde_json::from_str::<serde_json::Value>(&contents)
+        .map_err(|e| e.to_string())
+        .and_then(migrate);
+    match save_file {
+        Ok(save_file) => save_file,
+        Err(e) => {
+            // Starting over would overwrite it, so set it aside first
+            warn!("Could not load the save file, keeping a backup: {}", e);
+            if let Err(e) = write_storage(BACKUP_FILE, &contents) {
+                warn!("Could not back up the save file: {}", e);
+            }
+            SaveFile::default()
+        }
+    }
+}
+
+/// `MIGRATIONS[n - 1]` upgrades a version `n` save to version `n + 1`.
+const MIGRATIONS: [fn(&mut serde_json::Value); SAVE_VERSION as usize - 1] =
+    [add_run_difficulty, add_profile, add_run_taxi];
+
+// Version 2: runs keep the difficulty they were started on.
+fn add_run_difficulty(value: &mut serde_json::Value) {
+    if let Some(run) = value.get_mut("run").and_then(|run| run.as_object_mut()) {
+        run.insert(
+            String::from("difficulty"),
+            serde_json::to_value(difficulty::Difficulty::Normal).unwrap(),
+        );
+    }
+}
+
+// Version 3: the driver's rating is kept across runs.
+fn add_profile(value: &mut serde_json::Value) {
+    if let Some(save) = value.as_object_mut() {
+        save.insert(
+            String::from("profile"),
+            serde_json::to_value(Profile::default()).unwrap(),
+        );
+    }
+}
+
+// Version 4: runs keep the taxi as it was, older ones continue on a full
+// tank and without damage.
+fn add_run_taxi(value: &mut serde_json::Value) {
+    if let Some(run) = value.get_mut("run").and_then(|run| run.as_object_mut()) {
+        run.insert(
+            String::from("fuel"),
+            serde_json::Value::from(tuning::GameTuning::default().fuel.tank),
+        );
+        run.insert(String::from("damage"), serde_json::Value::from(0.));
+    }
+}
+
+// Upgrades older saves one version at a time until they match SAVE_VERSION.
+fn migrate(mut value: serde_json::Value) -> Result<SaveFile, String> {
+    let version = value
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .ok_or("the save file has no version")?;
+    if version == 0 || version > SAVE_VERSION as u64 {
+        return Err(format!("unknown save version {}", version));
+    }
+
+    for (step, upgrade) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        upgrade(&mut value);
+        value["version"] = serde_json::Value::from(step + 2);
+    }
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+fn write(save_file: &SaveFile) {
+    write_json(SAVE_FILE, save_file);
+}
+
+/// Writes `value` as pretty JSON to `name` in the game's storage, logging failures.
+pub fn write_json<T: Serialize>(name: &str, value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(contents) => {
+            if let Err(e) = write_storage(name, &contents) {
+                warn!("Could not write {}: {}", name, e);
+            }
+        }
+        Err(e) => warn!("Could not serialize {}: {}", name, e),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn storage_path(name: &str) -> Option<std::path::PathBuf> {
+    // Tests never touch the player's own saves
+    if cfg!(test) {
+        return Some(std::env::temp_dir().join("taxigt-test").join(name));
+    }
+    dirs::config_dir().map(|dir| dir.join("taxigt").join(name))
+}
+
+/// Reads `name` from the config directory on native and from localStorage on the web.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(name: &str) -> Option<String> {
+    std::fs::read_to_string(storage_path(name)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_storage(name: &str, contents: &str) -> Result<(), String> {
+    let path = storage_path(name).ok_or("no config directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(name: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("taxigt-{}", name))
+        .ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_storage(name: &str, contents: &str) -> Result<(), String> {
+    local_storage()
+        .ok_or("no localStorage")?
+        .set_item(&format!("taxigt-{}", name), contents)
+        .map_err(|e| format!("{:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::*;
+    use crate::ResetGame;
+    use bevy::ecs::system::RunSystemOnce;
+
+    // Checkpoints the run once `wear` has been done to the taxi, starts over
+    // on a new taxi and continues the saved run from the menu.
+    fn continue_after(wear: impl FnOnce(&mut PlayerCar)) -> App {
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+        wear(
+            &mut app
+                .world_mut()
+                .query::<&mut PlayerCar>()
+                .single_mut(app.world_mut()),
+        );
+        app.world_mut()
+            .run_system_once(
+                |mut save_game: ResMut<SaveGame>,
+                 player_data: Res<PlayerHealth>,
+                 player_car_query: Query<&PlayerCar>,
+                 events: Res<OccuredEvents>,
+                 posessions: Res<Posessions>| {
+                    save_game.checkpoint(
+                        &player_data,
+                        player_car_query.single(),
+                        &events,
+                        &posessions,
+                    );
+                },
+            )
+            .unwrap();
+
+        app.world_mut().resource_mut::<ResetGame>().0 = true;
+        run(&mut app, 1);
+        app.insert_resource(ContinueGame(true))
+            .init_resource::<PendingUpgrades>();
+        app.world_mut()
+            .run_system_once(continue_game_system)
+            .unwrap();
+        app
+    }
+
+    fn taxi(app: &mut App) -> &PlayerCar {
+        app.world_mut().query::<&PlayerCar>().single(app.world())
+    }
+
+    #[test]
+    fn continued_runs_keep_the_fuel_left() {
+        let mut app = continue_after(|player_car| player_car.fuel = 4.5);
+        assert_eq!(taxi(&mut app).fuel, 4.5);
+    }
+
+    #[test]
+    fn continued_runs_keep_the_damage_until_it_is_repaired() {
+        let mut app = continue_after(|player_car| player_car.damage = 35.);
+        assert_eq!(taxi(&mut app).damage, 35.);
+    }
+
+    #[test]
+    fn current_saves_load_as_they_are() {
+        let mut save_file = SaveFile::default();
+        save_file.settings.language = String::from("es");
+        let value = serde_json::to_value(&save_file).unwrap();
+
+        let loaded = migrate(value).unwrap();
+        assert_eq!(loaded.version, SAVE_VERSION);
+        assert_eq!(loaded.settings.language, "es");
+    }
+
+    #[test]
+    fn version_1_runs_continue_on_normal() {
+        let value = serde_json::json!({
+            "version": 1,
+            "settings": serde_json::to_value(Settings::default()).unwrap(),
+            "run": {
+                "cycles_completed": 2,
+                "time_limit_required_earnings": 40.0,
+                "total_earnings": 55.0,
+                "spent": 10.0,
+                "distance_traveled": 12.5,
+                "events": [],
+                "posessions": [],
+            },
+        });
+
+        let loaded = migrate(value).unwrap();
+        assert_eq!(loaded.version, SAVE_VERSION);
+        let run = loaded.run.unwrap();
+        assert_eq!(run.difficulty, difficulty::Difficulty::Normal);
+        assert_eq!(run.cycles_completed, 2);
+    }
+
+    #[test]
+    fn version_2_saves_start_with_an_unrated_driver() {
+        let mut value = serde_json::to_value(SaveFile::default()).unwrap();
+        value["version"] = serde_json::Value::from(2);
+        value.as_object_mut().unwrap().remove("profile");
+
+        let loaded = migrate(value).unwrap();
+        assert_eq!(loaded.version, SAVE_VERSION);
+        assert_eq!(loaded.profile.rated_rides, 0);
+        assert_eq!(loaded.profile.rating(), None);
+    }
+
+    #[test]
+    fn version_3_runs_continue_on_a_full_tank_without_damage() {
+        let save_file = SaveFile {
+            run: Some(RunProgress::default()),
+            ..default()
+        };
+        let mut value = serde_json::to_value(save_file).unwrap();
+        value["version"] = serde_json::Value::from(3);
+        let run = value["run"].as_object_mut().unwrap();
+        run.remove("fuel");
+        run.remove("damage");
+
+        let loaded = migrate(value).unwrap();
+        assert_eq!(loaded.version, SAVE_VERSION);
+        let run = loaded.run.unwrap();
+        assert_eq!(run.fuel, tuning::GameTuning::default().fuel.tank);
+        assert_eq!(run.damage, 0.);
+    }
+
+    #[test]
+    fn saves_without_a_known_version_are_rejected() {
+        let mut value = serde_json::to_value(SaveFile::default()).unwrap();
+        value["version"] = serde_json::Value::from(SAVE_VERSION + 1);
+        assert!(migrate(value.clone()).is_err());
+
+        value.as_object_mut().unwrap().remove("version");
+        assert!(migrate(value).is_err());
+    }
+}
@@ -0,0 +1,392 @@
+use crate::structured_dialog;
+use crate::structured_dialog::{Actions, Choice, ChoiceDialog, Dialog, Language};
+use crate::tuning::GameTuning;
+use crate::AppState;
+use crate::Controls;
+use crate::CurrentSelection;
+use crate::PlayerCar;
+use crate::PlayerHealth;
+use crate::PlayerMarker;
+use crate::Posessions;
+use crate::RoadsideObject;
+use crate::ShopMarker;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_common_assets::json::JsonAssetPlugin;
+use std::time::Duration;
+
+pub const SHOP_DIALOG: &str = "shop";
+pub const NOT_ENOUGH_MONEY_DIALOG: &str = "not enough money";
+const LEAVE_CHOICE: &str = "leave";
+const REPAIR_CHOICE: &str = "repair";
+
+#[derive(serde::Deserialize, Asset, TypePath, Debug, Default, Clone)]
+pub struct ShopCatalogue {
+    pub upgrades: Vec<Upgrade>,
+}
+
+/// A one-time purchase. Each effect is added to the matching `PlayerCar` stat.
+#[derive(serde::Deserialize, Asset, TypePath, Debug, Default, Clone)]
+pub struct Upgrade {
+    pub id: String,
+    pub language: Language,
+    pub price: f32,
+    #[serde(default)]
+    pub acceleration: f32,
+    #[serde(default)]
+    pub braking: f32,
+    #[serde(default)]
+    pub top_speed: f32,
+    /// Seconds taken off the wait between lane changes
+    #[serde(default)]
+    pub lane_change: f32,
+    /// Extra tip percentage offered by every new passenger
+    #[serde(default)]
+    pub comfort: f32,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct ShopHandle(pub Handle<ShopCatalogue>);
+
+/// The upgrade catalogue loaded from `shop.json`.
+#[derive(SystemParam)]
+pub struct Catalogue<'w> {
+    handle: Res<'w, ShopHandle>,
+    catalogues: Res<'w, Assets<ShopCatalogue>>,
+}
+
+impl Catalogue<'_> {
+    /// None until `shop.json` has loaded.
+    pub fn get(&self) -> Option<&ShopCatalogue> {
+        self.catalogues.get(&self.handle.0)
+    }
+}
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(JsonAssetPlugin::<ShopCatalogue>::new(&[".json"]))
+            .add_systems(Startup, load_catalogue)
+            .add_systems(
+                Update,
+                (shop_open_system, shop_purchase_system).run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+fn load_catalogue(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ShopHandle(asset_server.load("shop.json")));
+}
+
+/// Opens the shop when the taxi stops in the bottom lane in front of a mechanic shop.
+fn shop_open_system(
+    tuning: Res<GameTuning>,
+    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
+    dialog_engine: structured_dialog::DialogEngine,
+    catalogue: Catalogue,
+    player_query: Query<(&Transform, &PlayerCar), With<PlayerMarker>>,
+    shop_query: Query<(Entity, &RoadsideObject), With<ShopMarker>>,
+    mut visited_shop: Local<Option<Entity>>,
+) {
+    if dialog_message.dialog.is_some() {
+        return;
+    }
+
+    let (player_transform, player_car) = player_query.single();
+    let Some((shop_entity, _)) = shop_query.iter().find(|(_, roadside_object)| {
+        roadside_object.aabb.min.x < 0. && roadside_object.aabb.max.x > 0.
+    }) else {
+        *visited_shop = None;
+        return;
+    };
+
+    // Driving off, even just to turn around, makes the next stop a new one
+    if player_car.is_moving() {
+        *visited_shop = None;
+        return;
+    }
+    if player_transform.translation.y > -100. {
+        return;
+    }
+
+    // Only open once per stop
+    if *visited_shop == Some(shop_entity) {
+        return;
+    }
+    *visited_shop = Some(shop_entity);
+
+    let Some(catalogue) = catalogue.get() else {
+        return;
+    };
+    let repair_price = tuning.damage.repair_price(player_car.damage);
+    dialog_message.show(dialog_engine.dialog(SHOP_DIALOG).map(|dialog| {
+        with_catalogue(dialog, catalogue, &dialog_engine.posessions.0, repair_price)
+    }));
+}
+
+// Lists the upgrades not bought yet ahead of the dialog's own choices, and
+// only offers a repair when there is damage to fix.
+fn with_catalogue(
+    mut dialog: Dialog,
+    catalogue: &ShopCatalogue,
+    posessions: &[String],
+    repair_price: f32,
+) -> Dialog {
+    let mut choices: Vec<Choice> = catalogue
+        .upgrades
+        .iter()
+        .filter(|upgrade| !posessions.contains(&upgrade.id))
+        .map(|upgrade| Choice {
+            choice: upgrade.id.clone(),
+            dialog: ChoiceDialog {
+                language: priced(&upgrade.language, upgrade.price),
+                actions: Actions::default(),
+            },
+        })
+        .collect();
+    choices.extend(
+        dialog
+            .choices
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|choice| choice.choice != REPAIR_CHOICE || repair_price > 0.)
+            .map(|mut choice| {
+                if choice.choice == REPAIR_CHOICE {
+                    choice.dialog.language = priced(&choice.dialog.language, repair_price);
+                }
+                choice
+            }),
+    );
+    dialog.choices = Some(choices);
+    dialog
+}
+
+/// Appends the price to every translation, e.g. "Engine - 120".
+pub fn priced(language: &Language, price: f32) -> Language {
+    Language(
+        language
+            .0
+            .iter()
+            .map(|(locale, name)| (locale.clone(), format!("{} - {}", name, price)))
+            .collect::<HashMap<String, String>>(),
+    )
+}
+
+/// Why an upgrade wasn't sold.
+#[derive(Debug, PartialEq, Eq)]
+enum Refusal {
+    Owned,
+    NotEnoughMoney,
+}
+
+/// Pays for `upgrade` out of the total earnings and keeps it with the posessions.
+fn buy(
+    upgrade: &Upgrade,
+    player_data: &mut PlayerHealth,
+    posessions: &mut Posessions,
+) -> Result<(), Refusal> {
+    if posessions.0.contains(&upgrade.id) {
+        return Err(Refusal::Owned);
+    }
+    if player_data.total_earnings < upgrade.price {
+        return Err(Refusal::NotEnoughMoney);
+    }
+    player_data.total_earnings -= upgrade.price;
+    player_data.spent += upgrade.price;
+    posessions.0.push(upgrade.id.clone());
+    Ok(())
+}
+
+/// Says the player can't pay, keeping the choices up so they can pick
+/// something cheaper or leave.
+pub fn not_enough_money(
+    dialog_message: &mut structured_dialog::DialogMessage,
+    dialog_engine: &structured_dialog::DialogEngine,
+) {
+    let language = match dialog_engine.dialog(NOT_ENOUGH_MONEY_DIALOG) {
+        Some(line) => line.language,
+        None => Language(HashMap::from_iter([(
+            String::from(structured_dialog::DEFAULT_LOCALE),
+            String::from("Not enough coins"),
+        )])),
+    };
+    if let Some(dialog) = &mut dialog_message.dialog {
+        dialog.language = language;
+    }
+}
+
+/// The run a purchase is paid from. Purchases are saved with the next round's
+/// checkpoint, so a continued run never keeps money earned mid-round.
+#[derive(SystemParam)]
+struct Buyer<'w> {
+    player_data: ResMut<'w, PlayerHealth>,
+    dialog_engine: structured_dialog::DialogEngine<'w>,
+}
+
+fn shop_purchase_system(
+    controls: Controls,
+    current_selection: Res<CurrentSelection>,
+    mut dialog_message: ResMut<structured_dialog::DialogMessage>,
+    catalogue: Catalogue,
+    buyer: Buyer,
+    mut player_query: Query<&mut PlayerCar, With<PlayerMarker>>,
+    tuning: Res<GameTuning>,
+) {
+    let Buyer {
+        mut player_data,
+        mut dialog_engine,
+    } = buyer;
+    match &dialog_message.dialog {
+        Some(dialog) if dialog.id == SHOP_DIALOG => {}
+        _ => return,
+    }
+
+    if !controls.confirm_just_pressed() {
+        return;
+    }
+
+    if current_selection.0 == LEAVE_CHOICE {
+        dialog_message.show(None);
+        return;
+    }
+
+    if current_selection.0 == REPAIR_CHOICE {
+        for mut player_car in player_query.iter_mut() {
+            let price = tuning.damage.repair_price(player_car.damage);
+            if player_data.total_earnings < price {
+                not_enough_money(&mut dialog_message, &dialog_engine);
+                return;
+            }
+            player_data.total_earnings -= price;
+            player_data.spent += price;
+            player_car.damage = 0.0;
+        }
+        dialog_message.show(None);
+        return;
+    }
+
+    let Some(upgrade) = catalogue.get().and_then(|catalogue| {
+        catalogue
+            .upgrades
+            .iter()
+            .find(|u| u.id == current_selection.0)
+    }) else {
+        return;
+    };
+
+    match buy(upgrade, &mut player_data, &mut dialog_engine.posessions) {
+        Ok(()) => {}
+        Err(Refusal::NotEnoughMoney) => {
+            not_enough_money(&mut dialog_message, &dialog_engine);
+            return;
+        }
+        Err(Refusal::Owned) => return,
+    }
+    for mut player_car in player_query.iter_mut() {
+        apply_upgrade(&mut player_car, upgrade);
+    }
+    dialog_message.show(None);
+}
+
+pub fn apply_upgrade(player_car: &mut PlayerCar, upgrade: &Upgrade) {
+    player_car.acceleration += upgrade.acceleration;
+    player_car.braking += upgrade.braking;
+    player_car.top_speed += upgrade.top_speed;
+    player_car.comfort += upgrade.comfort;
+
+    let lane_change =
+        (player_car.rate_limit_up.duration().as_secs_f32() - upgrade.lane_change).max(0.05);
+    player_car
+        .rate_limit_up
+        .set_duration(Duration::from_secs_f32(lane_change));
+    player_car
+        .rate_limit_down
+        .set_duration(Duration::from_secs_f32(lane_change));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::bounding::Aabb2d;
+
+    fn taxi() -> PlayerCar {
+        PlayerCar {
+            aabb: Aabb2d::new(Vec2::ZERO, Vec2::new(44.5, 26.5)),
+            speed_coeff: 0.,
+            timer: Timer::from_seconds(0.075, TimerMode::Repeating),
+            rate_limit_up: Timer::from_seconds(0.25, TimerMode::Once),
+            rate_limit_down: Timer::from_seconds(0.25, TimerMode::Once),
+            atlas_right: (0, 2),
+            atlas_left: (3, 5),
+            acceleration: 1.0,
+            braking: 1.0,
+            top_speed: 1.0,
+            comfort: 0.0,
+            fuel: 30.,
+            damage: 0.0,
+            skid: 0.0,
+            u_turn: None,
+        }
+    }
+
+    fn upgrade(id: &str, price: f32) -> Upgrade {
+        Upgrade {
+            id: id.to_string(),
+            price,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn upgrades_add_to_the_taxi_and_lane_changes_stay_above_the_floor() {
+        let mut player_car = taxi();
+        let engine = Upgrade {
+            acceleration: 0.25,
+            top_speed: 0.1,
+            comfort: 2.,
+            lane_change: 0.1,
+            ..upgrade("engine", 120.)
+        };
+        apply_upgrade(&mut player_car, &engine);
+        assert_eq!(player_car.acceleration, 1.25);
+        assert_eq!(player_car.braking, 1.0);
+        assert_eq!(player_car.top_speed, 1.1);
+        assert_eq!(player_car.comfort, 2.);
+        assert!((player_car.rate_limit_up.duration().as_secs_f32() - 0.15).abs() < 0.001);
+
+        // However many are stacked, changing lanes never gets instant
+        apply_upgrade(&mut player_car, &engine);
+        apply_upgrade(&mut player_car, &engine);
+        assert_eq!(player_car.rate_limit_up.duration().as_secs_f32(), 0.05);
+        assert_eq!(player_car.rate_limit_down.duration().as_secs_f32(), 0.05);
+    }
+
+    #[test]
+    fn buying_pays_for_each_upgrade_once() {
+        let mut player_data = PlayerHealth {
+            total_earnings: 150.,
+            ..default()
+        };
+        let mut posessions = Posessions(vec![]);
+
+        let brakes = upgrade("brakes", 100.);
+        assert_eq!(buy(&brakes, &mut player_data, &mut posessions), Ok(()));
+        assert_eq!(player_data.total_earnings, 50.);
+        assert_eq!(player_data.spent, 100.);
+        assert_eq!(posessions.0, ["brakes"]);
+
+        assert_eq!(
+            buy(&brakes, &mut player_data, &mut posessions),
+            Err(Refusal::Owned)
+        );
+        assert_eq!(
+            buy(&upgrade("engine", 120.), &mut player_data, &mut posessions),
+            Err(Refusal::NotEnoughMoney)
+        );
+        assert_eq!(player_data.total_earnings, 50.);
+        assert_eq!(player_data.spent, 100.);
+        assert_eq!(posessions.0, ["brakes"]);
+    }
+}
@@ -0,0 +1,509 @@
+use crate::damage;
+use crate::difficulty;
+use crate::fuel;
+use crate::hazard;
+use crate::meter;
+use crate::patience;
+use crate::places;
+use crate::rating;
+use crate::receipt;
+use crate::ride;
+use crate::route;
+use crate::structured_dialog;
+use crate::traffic;
+use crate::AppState;
+use crate::CurrentSelection;
+use crate::InteractionRateLimit;
+use crate::OccuredEvents;
+use crate::PlayerHealth;
+use crate::Posessions;
+use crate::ResetGame;
+use crate::ResumeGame;
+use crate::SpawnThingTimer;
+use crate::Taxi;
+use bevy::prelude::*;
+
+/// Driving, traffic, passengers and rounds, without anything that needs a
+/// window, GPU or audio. Runs under `MinimalPlugins` as long as there is an
+/// `AssetServer` and the resources from the dialog, save, leaderboard, daily,
+/// rng and tuning modules.
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ride::RidePlugin,
+            fuel::FuelPlugin,
+            damage::DamagePlugin,
+            hazard::HazardPlugin,
+            traffic::TrafficPlugin,
+            patience::PatiencePlugin,
+            places::PlacesPlugin,
+            route::RoutePlugin,
+            meter::MeterPlugin,
+            receipt::ReceiptPlugin,
+            rating::RatingPlugin,
+        ))
+        .insert_resource(ResetGame(false))
+        .insert_resource(ResumeGame {
+            resume: false,
+            pause: true,
+        })
+        .insert_resource(PlayerHealth::default())
+        .init_resource::<difficulty::Difficulty>()
+        .insert_resource(Taxi { ..default() })
+        .insert_resource(CurrentSelection(String::new()))
+        .insert_resource(InteractionRateLimit(Timer::from_seconds(
+            0.20,
+            TimerMode::Once,
+        )))
+        .insert_resource(OccuredEvents(vec![]))
+        .insert_resource(Posessions(vec![]))
+        .init_resource::<structured_dialog::DialogMessage>()
+        .insert_resource(SpawnThingTimer {
+            timer: Timer::from_seconds(0.2, TimerMode::Repeating),
+            allow_shop: false,
+        })
+        .add_systems(
+            OnEnter(AppState::Game),
+            (difficulty::start_run_system, crate::spawn_player),
+        )
+        .add_systems(
+            Update,
+            (
+                crate::game_level_system,
+                crate::road_system,
+                crate::road_line_system,
+                crate::car_intersection_system,
+                crate::reset,
+                crate::shop_spawn_system,
+                crate::person_spawn_system,
+                crate::movement_input_system,
+                crate::dialog_choice_selection_system,
+                structured_dialog::dialog_actions_system,
+            )
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+/// Runs `GameplayPlugin` headless and scripts the player, for the gameplay
+/// tests here and next to each gameplay module.
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use crate::daily;
+    use crate::leaderboard;
+    use crate::personality::Personality;
+    use crate::rng;
+    use crate::save;
+    use crate::structured_dialog::{DialogRegistry, GameScript};
+    use crate::tuning::GameTuning;
+    use crate::{
+        DisplayLanguage, Passenger, PersonHighlightMarker, PersonMarker, PlayerCar, PlayerMarker,
+        Ride, RoadsideObject, PERSON_Y_BOTTOM,
+    };
+    use bevy::math::bounding::Aabb2d;
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    pub const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+    /// The `dialog.json` the game ships with.
+    pub fn shipped_script() -> GameScript {
+        serde_json::from_str(include_str!("../assets/dialog.json")).unwrap()
+    }
+
+    pub fn headless_app(game_script: &GameScript, seed: u64) -> App {
+        headless_app_at(game_script, seed, difficulty::Difficulty::Normal)
+    }
+
+    pub fn headless_app_at(
+        game_script: &GameScript,
+        seed: u64,
+        difficulty: difficulty::Difficulty,
+    ) -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            TransformPlugin,
+            StatesPlugin,
+        ))
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<Font>()
+        .init_resource::<ButtonInput<KeyCode>>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+        .init_state::<AppState>()
+        .insert_resource(DisplayLanguage(String::from(
+            structured_dialog::DEFAULT_LOCALE,
+        )))
+        .insert_resource(DialogRegistry::new(game_script))
+        .insert_resource(save::SaveGame::default())
+        .insert_resource(leaderboard::Leaderboard::default())
+        .insert_resource(leaderboard::NameEntry::default())
+        .insert_resource(daily::DailyChallenge::default())
+        .insert_resource(daily::DailyResults::default())
+        .insert_resource(rng::RunSeed(Some(seed)))
+        .insert_resource(rng::GameRng::from_seed(seed))
+        .insert_resource(GameTuning::default())
+        .insert_resource(difficulty)
+        .add_plugins(GameplayPlugin);
+
+        app.world_mut()
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Game);
+        app.update();
+        app
+    }
+
+    // Keeps random passengers, shops and traffic out of the way of a scripted test.
+    pub fn stop_spawning(app: &mut App) {
+        app.world_mut()
+            .resource_mut::<SpawnThingTimer>()
+            .timer
+            .set_duration(Duration::from_secs(60 * 60));
+    }
+
+    pub fn run(app: &mut App, frames: u32) {
+        for _ in 0..frames {
+            app.update();
+        }
+    }
+
+    pub fn hold(app: &mut App, key: KeyCode, frames: u32) {
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(key);
+        run(app, frames);
+        release(app, key);
+    }
+
+    pub fn release(app: &mut App, key: KeyCode) {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release(key);
+        keyboard.clear();
+    }
+
+    pub fn dialog_id(app: &App) -> Option<String> {
+        app.world()
+            .resource::<structured_dialog::DialogMessage>()
+            .dialog
+            .as_ref()
+            .map(|dialog| dialog.id.clone())
+    }
+
+    pub fn player_y(app: &mut App) -> f32 {
+        app.world_mut()
+            .query_filtered::<&Transform, With<PlayerMarker>>()
+            .single(app.world())
+            .translation
+            .y
+    }
+
+    pub fn ride(app: &App, who: Entity) -> &Ride {
+        let taxi = app.world().resource::<Taxi>();
+        taxi.rides.iter().find(|r| r.who == who).unwrap()
+    }
+
+    pub fn ride_state(app: &App, who: Entity) -> ride::RideState {
+        ride(app, who).state
+    }
+
+    // Drives at full speed until braking now stops the taxi at the rider's
+    // landmark, then lets it roll to a stop.
+    pub fn drive_to_the_stop(app: &mut App, rider: Entity) {
+        let tuning = GameTuning::default();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        for _ in 0..60 * 60 {
+            run(app, 1);
+            let car = app.world_mut().query::<&PlayerCar>().single(app.world());
+            let braking_distance = tuning.speed_x * car.speed_coeff.powi(2) / (2. * car.braking);
+            if ride(app, rider)
+                .stop_ahead
+                .is_some_and(|ahead| ahead <= braking_distance)
+            {
+                break;
+            }
+        }
+        release(app, KeyCode::Space);
+        run(app, 120);
+    }
+
+    // Drives at full speed until the rider's stop is out of reach behind the
+    // taxi, and keeps the gas held.
+    pub fn drive_past_the_stop(app: &mut App, rider: Entity) {
+        let reach = GameTuning::default().landmarks.dropoff_reach;
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        let mut frames = 0;
+        while !ride(app, rider)
+            .stop_ahead
+            .is_some_and(|ahead| ahead < -reach)
+        {
+            run(app, 1);
+            frames += 1;
+            assert!(frames < 60 * 60, "never reached the stop");
+        }
+    }
+
+    /// Pulls over into the bottom lane next to a new passenger, who asks for a
+    /// ride. Returns the passenger.
+    pub fn hail_passenger(app: &mut App, personality: Personality) -> Entity {
+        hold(app, KeyCode::ArrowDown, 20);
+        spawn_passenger(app, "Ada Lovelace", personality);
+        run(app, 30);
+        app.world().resource::<Taxi>().closest_person.unwrap()
+    }
+
+    /// Hails a new passenger and takes the job. Returns the new rider.
+    pub fn pick_up_passenger(app: &mut App, personality: Personality) -> Entity {
+        hail_passenger(app, personality);
+        hold(app, KeyCode::Enter, 1);
+        *app.world().resource::<Taxi>().riders.last().unwrap()
+    }
+
+    pub fn spawn_passenger(app: &mut App, name: &str, personality: Personality) {
+        let passenger = Passenger {
+            name: name.to_string(),
+            sprite_index: 0,
+            personality,
+        };
+        app.world_mut()
+            .spawn((
+                PersonMarker,
+                RoadsideObject {
+                    aabb: Aabb2d::new(Vec2::new(0., PERSON_Y_BOTTOM), Vec2::new(4.5, 11.)),
+                },
+                passenger.clone(),
+                Transform::from_xyz(0., PERSON_Y_BOTTOM, 0.),
+                Visibility::Visible,
+            ))
+            .with_children(|p| {
+                p.spawn((
+                    PersonHighlightMarker,
+                    passenger,
+                    Transform::default(),
+                    Visibility::Hidden,
+                ));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::*;
+    use super::*;
+    use crate::personality::Personality;
+    use crate::structured_dialog::GameScript;
+    use crate::tuning::GameTuning;
+    use crate::{Car, CarMarker, Passenger, PersonMarker, PlayerCar};
+    use bevy::math::bounding::Aabb2d;
+
+    #[derive(Resource, Default)]
+    struct RideLog(Vec<&'static str>);
+
+    fn log_ride_events(
+        mut log: ResMut<RideLog>,
+        mut offered: EventReader<ride::RideOffered>,
+        mut accepted: EventReader<ride::RideAccepted>,
+        mut completed: EventReader<ride::RideCompleted>,
+    ) {
+        log.0.extend(offered.read().map(|_| "offered"));
+        log.0.extend(accepted.read().map(|_| "accepted"));
+        log.0.extend(completed.read().map(|_| "completed"));
+    }
+
+    #[test]
+    fn drives_a_ride_from_pickup_to_tip() {
+        let mut app = headless_app(&shipped_script(), 7);
+        app.init_resource::<RideLog>()
+            .add_systems(Update, log_ride_events);
+        stop_spawning(&mut app);
+
+        // Pulled over into the bottom lane, next to the curb
+        hail_passenger(&mut app, Personality::Easygoing);
+        let lane_height = GameTuning::default().lane_height;
+        assert_eq!(player_y(&mut app), -lane_height / 2. - lane_height);
+        assert_eq!(
+            dialog_id(&app).as_deref(),
+            Some(structured_dialog::RIDE_DIALOG)
+        );
+        assert_eq!(app.world().resource::<CurrentSelection>().0, "0");
+
+        hold(&mut app, KeyCode::Enter, 1);
+        let rider = app.world().resource::<Taxi>().riders[0];
+        assert_eq!(ride_state(&app, rider), ride::RideState::Accepted);
+        let distance = ride(&app, rider).distance;
+        assert!(distance > 0.);
+
+        drive_to_the_stop(&mut app, rider);
+
+        assert_eq!(
+            dialog_id(&app).as_deref(),
+            Some(structured_dialog::BYE_DIALOG)
+        );
+        let taxi = app.world().resource::<Taxi>();
+        let ride = taxi.rides.iter().find(|r| r.who == rider).unwrap();
+        assert_eq!(ride.state, ride::RideState::Paid);
+        assert_eq!(
+            ride.tip,
+            (ride.trip_cost * (ride.tip_percentage / 100.))
+                .max(0.)
+                .floor()
+        );
+        let paid = ride.trip_cost + ride.tip;
+        assert_eq!(app.world().resource::<PlayerHealth>().total_earnings, paid);
+        assert_eq!(
+            app.world().resource::<RideLog>().0,
+            ["offered", "accepted", "completed"]
+        );
+
+        hold(&mut app, KeyCode::Enter, 1);
+        assert!(app.world().resource::<Taxi>().riders.is_empty());
+    }
+
+    #[test]
+    fn pooled_riders_pay_less_and_get_out_at_their_own_stops() {
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+        let ada = pick_up_passenger(&mut app, Personality::Easygoing);
+        let solo_fare = ride(&app, ada).trip_cost;
+        assert!(!ride(&app, ada).pooled);
+
+        // A second passenger gets the shared fare, and so does the first
+        spawn_passenger(&mut app, "Grace Hopper", Personality::Easygoing);
+        run(&mut app, 30);
+        assert_eq!(
+            dialog_id(&app).as_deref(),
+            Some(structured_dialog::RIDE_DIALOG)
+        );
+        hold(&mut app, KeyCode::Enter, 1);
+        let riders = app.world().resource::<Taxi>().riders.clone();
+        assert_eq!(riders.len(), 2);
+        let grace = riders[1];
+        assert!(ride(&app, ada).pooled && ride(&app, grace).pooled);
+        let pooling = GameTuning::default().pooling;
+        assert_eq!(ride(&app, ada).trip_cost, pooling.pooled_fare(solo_fare));
+
+        // Whoever's stop comes first gets out, the other stays on board
+        let (first, second) = if ride(&app, ada).distance <= ride(&app, grace).distance {
+            (ada, grace)
+        } else {
+            (grace, ada)
+        };
+        drive_to_the_stop(&mut app, first);
+        assert_eq!(ride_state(&app, first), ride::RideState::Paid);
+        assert!(ride_state(&app, second).is_underway());
+        assert_eq!(
+            dialog_id(&app).as_deref(),
+            Some(structured_dialog::BYE_DIALOG)
+        );
+
+        hold(&mut app, KeyCode::Enter, 1);
+        assert_eq!(app.world().resource::<Taxi>().riders, [second]);
+    }
+
+    #[test]
+    fn passing_the_stop_away_from_the_curb_clears_drop_off_soon() {
+        let mut app = headless_app(&shipped_script(), 7);
+        stop_spawning(&mut app);
+        let rider = pick_up_passenger(&mut app, Personality::Easygoing);
+
+        // Leave the curb, then drive until the stop is coming up
+        hold(&mut app, KeyCode::ArrowUp, 20);
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        let mut frames = 0;
+        while ride_state(&app, rider) != ride::RideState::Approaching {
+            run(&mut app, 1);
+            frames += 1;
+            assert!(frames < 60 * 60, "never got close to the stop");
+        }
+        release(&mut app, KeyCode::Space);
+        run(&mut app, 120);
+        assert_eq!(
+            dialog_id(&app).as_deref(),
+            Some(structured_dialog::DROP_OFF_SOON_DIALOG)
+        );
+
+        // Keep going past it, the passenger now says to stop here instead
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        while ride_state(&app, rider) != ride::RideState::Overshot {
+            run(&mut app, 1);
+            frames += 1;
+            assert!(frames < 60 * 60, "never passed the stop");
+        }
+        assert_eq!(
+            dialog_id(&app).as_deref(),
+            Some(structured_dialog::HERE_DIALOG)
+        );
+    }
+
+    #[test]
+    fn stopped_car_ahead_blocks_the_taxi() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        stop_spawning(&mut app);
+
+        let y = -GameTuning::default().lane_height / 2.;
+        let car = app
+            .world_mut()
+            .spawn((
+                CarMarker,
+                Car::new(
+                    Aabb2d::new(Vec2::new(60., y), Vec2::new(89. / 2., 53. / 2.)),
+                    0.,
+                ),
+                Transform::from_xyz(60., y, 0.),
+            ))
+            .id();
+
+        hold(&mut app, KeyCode::Space, 30);
+        let car = app.world().get::<Car>(car).unwrap();
+        assert!(car.intersects_player);
+        assert!(car.blocks_player_movement);
+        let player_car = app.world_mut().query::<&PlayerCar>().single(app.world());
+        assert!(player_car.speed_coeff < 0.5);
+    }
+
+    #[test]
+    fn missing_the_target_ends_the_game() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        stop_spawning(&mut app);
+
+        run(&mut app, 60 * 60 + 2);
+        assert_eq!(
+            dialog_id(&app).as_deref(),
+            Some(structured_dialog::GAME_OVER_DIALOG)
+        );
+    }
+
+    #[test]
+    fn same_seed_spawns_the_same_passengers() {
+        let passengers = |seed| {
+            let mut app = headless_app(&GameScript::default(), seed);
+            run(&mut app, 60 * 5);
+            let mut names: Vec<String> = app
+                .world_mut()
+                .query_filtered::<&Passenger, With<PersonMarker>>()
+                .iter(app.world())
+                .map(|p| p.name.clone())
+                .collect();
+            names.sort();
+            names
+        };
+
+        let first = passengers(7);
+        assert!(!first.is_empty());
+        assert_eq!(first, passengers(7));
+        assert_ne!(first, passengers(8));
+    }
+}
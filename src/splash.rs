@@ -1,3 +1,4 @@
+use crate::structured_dialog;
 use crate::util;
 use crate::AppState;
 use crate::DisplayLanguage;
@@ -14,7 +15,10 @@ pub struct SplashPlugin;
 impl Plugin for SplashPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::Splash), splash_setup)
-            .add_systems(Update, countdown.run_if(in_state(AppState::Splash)))
+            .add_systems(
+                Update,
+                (countdown, made_with_text_system).run_if(in_state(AppState::Splash)),
+            )
             .add_systems(
                 OnExit(AppState::Splash),
                 util::despawn_screen::<OnSplashScreen>,
@@ -25,11 +29,14 @@ impl Plugin for SplashPlugin {
 #[derive(Component)]
 struct SplashCamera;
 
+/// The "Made with Bevy" line, filled in once `dialog.json` has loaded.
+#[derive(Component)]
+struct MadeWithText;
+
 fn splash_setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut bg: ResMut<ClearColor>,
-    display_language: Res<DisplayLanguage>,
 ) {
     bg.0 = Color::BLACK;
     info!("Splash");
@@ -110,11 +117,6 @@ fn splash_setup(
             },
         ))
         .with_children(|p| {
-            let text = if display_language.0 == "english" {
-                "Made with Bevy"
-            } else {
-                "Hecho con Bevy"
-            };
             p.spawn((
                 RenderLayers::layer(2),
                 OnSplashScreen,
@@ -156,7 +158,8 @@ fn splash_setup(
                             font_size: 28.0,
                             ..default()
                         },
-                        TextSpan::new(text),
+                        MadeWithText,
+                        TextSpan::default(),
                     ));
                 });
             });
@@ -172,3 +175,20 @@ fn countdown(
         app_state.set(AppState::Menu);
     }
 }
+
+// The registry is still empty the first time the splash screen opens
+fn made_with_text_system(
+    display_language: Res<DisplayLanguage>,
+    dialog_registry: Res<structured_dialog::DialogRegistry>,
+    mut text_query: Query<(&mut TextSpan, Ref<MadeWithText>)>,
+) {
+    for (mut text_span, marker) in text_query.iter_mut() {
+        if !marker.is_added() && !dialog_registry.is_changed() {
+            continue;
+        }
+        text_span.0 = match dialog_registry.get("made with") {
+            Some(dialog) => dialog.language.text(&display_language).to_string(),
+            None => String::from("Made with Bevy"),
+        };
+    }
+}
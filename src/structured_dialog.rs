@@ -1,7 +1,36 @@
 use super::*;
+use bevy::ecs::system::SystemParam;
+use bevy::utils::HashMap;
+use std::collections::BTreeMap;
+
+/// Text missing a translation falls back to this locale.
+pub const DEFAULT_LOCALE: &str = "en";
+/// Menu action that switches `DisplayLanguage` to the selected choice's locale.
+pub const SET_LOCALE_EVENT: &str = "set_locale";
+/// Keeps the language menu's locale choices apart from authored ones like "back".
+const LOCALE_CHOICE_PREFIX: &str = "locale:";
+pub const LANGUAGE_DIALOG: &str = "language";
+
+pub const RIDE_DIALOG: &str = "ride";
+pub const DROP_OFF_SOON_DIALOG: &str = "drop off soon";
+pub const HERE_DIALOG: &str = "here";
+pub const BYE_DIALOG: &str = "bye";
+pub const GAME_OVER_DIALOG: &str = "game over";
+
+// The ride flow can't continue without these, so they fall back to built-in text.
+const REQUIRED_DIALOGS: [&str; 5] = [
+    RIDE_DIALOG,
+    DROP_OFF_SOON_DIALOG,
+    HERE_DIALOG,
+    BYE_DIALOG,
+    GAME_OVER_DIALOG,
+];
 
 #[derive(serde::Deserialize, Asset, TypePath, Debug, Default, Clone)]
 pub struct GameScript {
+    /// Locale code to the language's own name, e.g. `"es": "Español"`.
+    #[serde(default)]
+    pub locales: BTreeMap<String, String>,
     pub dialogs: Vec<Dialog>,
 }
 
@@ -26,9 +55,17 @@ pub struct Actions {
 }
 
 #[derive(serde::Deserialize, Asset, TypePath, Debug, Default, Clone)]
-pub struct Language {
-    pub spanish: String,
-    pub english: String,
+#[serde(transparent)]
+pub struct Language(pub HashMap<String, String>);
+
+impl Language {
+    pub fn text(&self, locale: &str) -> &str {
+        self.0
+            .get(locale)
+            .or_else(|| self.0.get(DEFAULT_LOCALE))
+            .map(|text| text.as_str())
+            .unwrap_or_default()
+    }
 }
 
 #[derive(serde::Deserialize, Asset, TypePath, Debug, Default, Clone)]
@@ -43,21 +80,603 @@ pub struct ChoiceDialog {
     pub actions: Actions,
 }
 
+/// Dialogs keyed by `id`, rebuilt whenever the `GameScript` asset is loaded.
+#[derive(Resource, Debug, Default)]
+pub struct DialogRegistry {
+    dialogs: HashMap<String, Vec<Dialog>>,
+}
+
+impl DialogRegistry {
+    pub fn new(game_script: &GameScript) -> Self {
+        let mut dialogs: HashMap<String, Vec<Dialog>> = HashMap::new();
+        for dialog in game_script.dialogs.iter() {
+            dialogs
+                .entry(dialog.id.clone())
+                .or_default()
+                .push(dialog.clone());
+        }
+
+        if let Some(variants) = dialogs.get_mut(LANGUAGE_DIALOG) {
+            let locales = locale_choices(game_script);
+            for dialog in variants.iter_mut() {
+                // The dialog's own choices, like "back", come after the locales
+                let authored = dialog.choices.take().unwrap_or_default();
+                dialog.choices = Some(locales.iter().cloned().chain(authored).collect());
+            }
+        }
+
+        Self { dialogs }
+    }
+
+    /// The first dialog with `id`, ignoring its requirements.
+    pub fn get(&self, id: &str) -> Option<&Dialog> {
+        self.dialogs.get(id).and_then(|variants| variants.first())
+    }
+
+    /// Finds the first dialog with `id` whose required events and posessions
+    /// are satisfied. Several dialogs may share an id to branch a conversation,
+    /// so the more specific variants should be listed first in `dialog.json`.
+    pub fn dialog(
+        &self,
+        id: &str,
+        events: &OccuredEvents,
+        posessions: &Posessions,
+    ) -> Option<Dialog> {
+        self.dialogs
+            .get(id)?
+            .iter()
+            .find(|d| d.is_available(events, posessions))
+            .cloned()
+    }
+}
+
+pub fn dialog_registry_system(
+    mut asset_events: EventReader<AssetEvent<GameScript>>,
+    game_script_asset: Res<Assets<GameScript>>,
+    mut registry: ResMut<DialogRegistry>,
+) {
+    for event in asset_events.read() {
+        let id = match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => id,
+            _ => continue,
+        };
+        let Some(game_script) = game_script_asset.get(*id) else {
+            continue;
+        };
+
+        *registry = DialogRegistry::new(game_script);
+        for required in REQUIRED_DIALOGS {
+            if registry.get(required).is_none() {
+                error!(
+                    "dialog.json has no \"{}\" dialog, using the built-in one",
+                    required
+                );
+            }
+        }
+    }
+}
+
+// One choice per locale in the asset. Without a `locales` table every locale
+// used by a dialog is offered under its code.
+fn locale_choices(game_script: &GameScript) -> Vec<Choice> {
+    let mut locales = game_script.locales.clone();
+    if locales.is_empty() {
+        for dialog in game_script.dialogs.iter() {
+            for locale in dialog.language.0.keys() {
+                locales.insert(locale.clone(), locale.clone());
+            }
+        }
+    }
+
+    locales
+        .into_iter()
+        .map(|(locale, name)| Choice {
+            choice: format!("{}{}", LOCALE_CHOICE_PREFIX, locale),
+            dialog: ChoiceDialog {
+                language: Language(HashMap::from_iter([(String::from(DEFAULT_LOCALE), name)])),
+                actions: Actions {
+                    events_changed_on_exit: vec![String::from(SET_LOCALE_EVENT)],
+                    ..default()
+                },
+            },
+        })
+        .collect()
+}
+
+/// The locale a language menu choice switches to.
+pub fn choice_locale(choice: &Choice) -> Option<&str> {
+    choice.choice.strip_prefix(LOCALE_CHOICE_PREFIX)
+}
+
+fn language(english: &str, spanish: &str) -> Language {
+    Language(HashMap::from_iter([
+        (String::from(DEFAULT_LOCALE), english.to_string()),
+        (String::from("es"), spanish.to_string()),
+    ]))
+}
+
+fn choice(choice: &str, text: Language) -> Choice {
+    Choice {
+        choice: choice.to_string(),
+        dialog: ChoiceDialog {
+            language: text,
+            actions: Actions::default(),
+        },
+    }
+}
+
+// Mirrors the shipped dialog.json so a broken asset doesn't strand the ride flow.
+fn fallback_dialog(id: &str) -> Dialog {
+    let (text, choices) = match id {
+        RIDE_DIALOG => (
+            language(
+                "Passenger: {person}\n\nTo: {destination} ({distance} km)\n\nPrice: {price} coins",
+                "Pasajero: {person}\n\nDestino: {destination} ({distance} km)\n\nPrecio: {price} monedas",
+            ),
+            Some(vec![
+                choice("0", language("Take the job", "Aceptar la tarea")),
+                choice("1", language("Leave the job", "Dejar la tarea")),
+            ]),
+        ),
+        DROP_OFF_SOON_DIALOG => (
+            language("My stop is coming up", "Mi parada se acerca"),
+            None,
+        ),
+        HERE_DIALOG => (language("I'll get out here", "Me bajaré aquí"), None),
+        BYE_DIALOG => (
+            language(
+                "Thanks, here you go.\n\n{person} gives you \n\n{price} coins & {tip} tip",
+                "Gracias, aquí tienes.\n\n{person} te da \n\n{price} monedas & {tip} propina",
+            ),
+            Some(vec![choice(
+                "thanks",
+                language("See you later", "Hasta luego"),
+            )]),
+        ),
+        GAME_OVER_DIALOG => (
+            language(
+                "Game Over\n\nRound: {level}\nTotal Collected: {total_collected}",
+                "Fin del Juego\n\nRonda: {level}\nTotal Recolectado: {total_collected}",
+            ),
+            Some(vec![choice("play again", language("Reset", "Reset"))]),
+        ),
+        _ => (language(id, id), None),
+    };
+
+    Dialog {
+        id: id.to_string(),
+        name: String::from("passenger"),
+        choices,
+        language: text,
+        ..default()
+    }
+}
+
+impl Dialog {
+    /// Identifies what the dialog looks like on screen, so a dialog rebuilt in
+    /// place with new text or choices gets redrawn.
+    pub fn display_key(&self) -> String {
+        let choices = self
+            .choices
+            .iter()
+            .flatten()
+            .map(|c| c.choice.as_str())
+            .collect::<Vec<&str>>()
+            .join(",");
+        format!(
+            "{}|{}|{}",
+            self.id,
+            self.language.text(DEFAULT_LOCALE),
+            choices
+        )
+    }
+
+    pub fn is_available(&self, events: &OccuredEvents, posessions: &Posessions) -> bool {
+        requirements_met(&self.events, &events.0)
+            && requirements_met(&self.posessions, &posessions.0)
+    }
+}
+
+impl Actions {
+    pub fn apply_on_enter(&self, events: &mut OccuredEvents, posessions: &mut Posessions) {
+        apply_changes(&self.events_changed_on_enter, &mut events.0);
+        apply_changes(&self.items_changed_on_enter, &mut posessions.0);
+    }
+
+    pub fn apply_on_exit(&self, events: &mut OccuredEvents, posessions: &mut Posessions) {
+        apply_changes(&self.events_changed_on_exit, &mut events.0);
+        apply_changes(&self.items_changed_on_exit, &mut posessions.0);
+    }
+}
+
+// A requirement prefixed with "!" must be absent, anything else must be present.
+fn requirements_met(required: &[String], present: &[String]) -> bool {
+    required.iter().all(|r| match r.strip_prefix('!') {
+        Some(absent) => !present.iter().any(|p| p == absent),
+        None => present.contains(r),
+    })
+}
+
+// A change prefixed with "!" removes the entry, anything else adds it once.
+fn apply_changes(changes: &[String], present: &mut Vec<String>) {
+    for change in changes.iter() {
+        match change.strip_prefix('!') {
+            Some(removed) => present.retain(|p| p != removed),
+            None => {
+                if !present.contains(change) {
+                    present.push(change.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Dialog lookups and the event/posession bookkeeping used by the in-game systems.
+#[derive(SystemParam)]
+pub struct DialogEngine<'w> {
+    registry: Res<'w, DialogRegistry>,
+    pub events: ResMut<'w, OccuredEvents>,
+    pub posessions: ResMut<'w, Posessions>,
+}
+
+impl DialogEngine<'_> {
+    pub fn dialog(&self, id: &str) -> Option<Dialog> {
+        self.registry.dialog(id, &self.events, &self.posessions)
+    }
+
+    pub fn ride(&self, personality: personality::Personality) -> Dialog {
+        self.passenger_dialog(RIDE_DIALOG, personality)
+    }
+
+    pub fn drop_off_soon(&self, personality: personality::Personality) -> Dialog {
+        self.passenger_dialog(DROP_OFF_SOON_DIALOG, personality)
+    }
+
+    pub fn here(&self, personality: personality::Personality) -> Dialog {
+        self.passenger_dialog(HERE_DIALOG, personality)
+    }
+
+    pub fn bye(&self, personality: personality::Personality) -> Dialog {
+        self.passenger_dialog(BYE_DIALOG, personality)
+    }
+
+    pub fn game_over(&self) -> Dialog {
+        self.required_dialog(GAME_OVER_DIALOG)
+    }
+
+    fn required_dialog(&self, id: &str) -> Dialog {
+        self.dialog(id).unwrap_or_else(|| fallback_dialog(id))
+    }
+
+    // Uses the text of the "<id> <personality>" dialog when there is one. The
+    // id and choices stay the same, so the ride flow works the same either way.
+    fn passenger_dialog(&self, id: &str, personality: personality::Personality) -> Dialog {
+        let mut dialog = self.required_dialog(id);
+        if let Some(line) = self.dialog(&format!("{} {}", id, personality.id())) {
+            dialog.language = line.language;
+        }
+        dialog
+    }
+
+    pub fn enter(&mut self, dialog: &Dialog) {
+        dialog
+            .actions
+            .apply_on_enter(&mut self.events, &mut self.posessions);
+    }
+
+    pub fn exit(&mut self, dialog: &Dialog) {
+        dialog
+            .actions
+            .apply_on_exit(&mut self.events, &mut self.posessions);
+    }
+
+    /// Applies the selected choice and returns the dialog it leads to, if any.
+    /// The choice's own `next_id` wins over the parent dialog's `next_id`.
+    pub fn choose(&mut self, dialog: &Dialog, choice: &Choice) -> Option<Dialog> {
+        let actions = &choice.dialog.actions;
+        actions.apply_on_enter(&mut self.events, &mut self.posessions);
+        actions.apply_on_exit(&mut self.events, &mut self.posessions);
+
+        let next_id = if !actions.next_id.is_empty() {
+            &actions.next_id
+        } else {
+            &dialog.actions.next_id
+        };
+        if next_id.is_empty() {
+            None
+        } else {
+            self.dialog(next_id)
+        }
+    }
+}
+
+/// Applies `actions` on enter and exit whenever the displayed in-game dialog changes.
+pub fn dialog_actions_system(
+    mut dialog_engine: DialogEngine,
+    dialog_message: Res<DialogMessage>,
+    mut previous_dialog: Local<Option<Dialog>>,
+    mut previous_shown: Local<u64>,
+) {
+    let previous_id = previous_dialog.as_ref().map(|d| d.id.clone());
+    let current_id = dialog_message.dialog.as_ref().map(|d| d.id.clone());
+    if previous_id == current_id && *previous_shown == dialog_message.shown {
+        return;
+    }
+    *previous_shown = dialog_message.shown;
+
+    if let Some(previous) = previous_dialog.take() {
+        dialog_engine.exit(&previous);
+    }
+    if let Some(current) = &dialog_message.dialog {
+        dialog_engine.enter(current);
+    }
+    *previous_dialog = dialog_message.dialog.clone();
+}
+
 #[derive(Resource, Debug, Default)]
 #[allow(dead_code)]
 pub struct DialogHandle(pub Handle<GameScript>);
 
-#[derive(Resource, Default, Clone)]
+#[derive(Resource, Clone)]
 pub struct DialogMessage {
     pub dialog: Option<Dialog>,
     pub selection_index: usize,
+    // Counts calls to `show`, so showing the same dialog twice counts as a change
+    shown: u64,
+}
+
+impl Default for DialogMessage {
+    fn default() -> Self {
+        Self {
+            dialog: None,
+            selection_index: 1,
+            shown: 0,
+        }
+    }
 }
 
 impl DialogMessage {
+    /// Puts `dialog` on screen and runs its actions, even when the same
+    /// dialog was already showing.
+    pub fn show(&mut self, dialog: Option<Dialog>) {
+        self.dialog = dialog;
+        self.shown += 1;
+    }
+
     pub fn reset(&mut self) {
         *self = Self {
-            selection_index: 1,
+            shown: self.shown + 1,
             ..default()
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::SystemState;
+
+    fn dialog(id: &str, name: &str, events: &[&str]) -> Dialog {
+        Dialog {
+            id: id.to_string(),
+            name: name.to_string(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            ..default()
+        }
+    }
+
+    fn registry(dialogs: Vec<Dialog>) -> DialogRegistry {
+        DialogRegistry::new(&GameScript {
+            dialogs,
+            ..default()
+        })
+    }
+
+    #[test]
+    fn registry_finds_the_first_variant_whose_requirements_are_met() {
+        let registry = registry(vec![
+            dialog("ride", "regular", &["met_before"]),
+            dialog("ride", "stranger", &["!met_before"]),
+            dialog("bye", "passenger", &[]),
+        ]);
+        let posessions = Posessions(vec![]);
+
+        let first_time = registry.dialog("ride", &OccuredEvents(vec![]), &posessions);
+        assert_eq!(first_time.unwrap().name, "stranger");
+        let met_before = OccuredEvents(vec![String::from("met_before")]);
+        let again = registry.dialog("ride", &met_before, &posessions);
+        assert_eq!(again.unwrap().name, "regular");
+
+        // `get` skips the requirements and returns the first variant
+        assert_eq!(registry.get("ride").unwrap().name, "regular");
+        assert!(registry.get("missing").is_none());
+        assert!(registry
+            .dialog("bye", &OccuredEvents(vec![]), &posessions)
+            .is_some());
+    }
+
+    #[test]
+    fn registry_has_no_dialog_when_no_variant_is_available() {
+        let registry = registry(vec![dialog("here", "passenger", &["late"])]);
+
+        let dialog = registry.dialog("here", &OccuredEvents(vec![]), &Posessions(vec![]));
+        assert!(dialog.is_none());
+    }
+
+    #[test]
+    fn language_menu_offers_each_locale_and_keeps_its_own_choices() {
+        let mut menu = dialog(LANGUAGE_DIALOG, "menu", &[]);
+        menu.choices = Some(vec![choice("back", language("Back", "Atrás"))]);
+        let registry = DialogRegistry::new(&GameScript {
+            locales: BTreeMap::from([
+                (String::from("en"), String::from("English")),
+                (String::from("es"), String::from("Español")),
+            ]),
+            dialogs: vec![menu],
+        });
+
+        let choices = registry.get(LANGUAGE_DIALOG).unwrap().choices.clone();
+        let choices = choices.unwrap();
+        let ids: Vec<&str> = choices.iter().map(|c| c.choice.as_str()).collect();
+        assert_eq!(ids, ["locale:en", "locale:es", "back"]);
+        let locales: Vec<Option<&str>> = choices.iter().map(choice_locale).collect();
+        assert_eq!(locales, [Some("en"), Some("es"), None]);
+    }
+
+    #[test]
+    fn fallback_dialogs_keep_the_ride_flow_going() {
+        for id in REQUIRED_DIALOGS {
+            let dialog = fallback_dialog(id);
+            assert_eq!(dialog.id, id);
+            assert!(
+                !dialog.language.text(DEFAULT_LOCALE).is_empty(),
+                "{} has no text",
+                id
+            );
+        }
+
+        let choices = |id| -> Vec<String> {
+            fallback_dialog(id)
+                .choices
+                .unwrap_or_default()
+                .into_iter()
+                .map(|c| c.choice)
+                .collect()
+        };
+        // Accepting a ride is choice "0"
+        assert_eq!(choices(RIDE_DIALOG), ["0", "1"]);
+        assert_eq!(choices(BYE_DIALOG).len(), 1);
+        assert_eq!(choices(GAME_OVER_DIALOG).len(), 1);
+        assert!(choices(HERE_DIALOG).is_empty());
+    }
+
+    #[test]
+    fn engine_uses_the_built_in_dialog_only_when_the_asset_has_none() {
+        let mut world = World::new();
+        world.insert_resource(registry(vec![dialog("ride", "from the asset", &[])]));
+        world.insert_resource(OccuredEvents(vec![]));
+        world.insert_resource(Posessions(vec![]));
+        let mut state = SystemState::<DialogEngine>::new(&mut world);
+        let engine = state.get_mut(&mut world);
+
+        assert_eq!(
+            engine.ride(personality::Personality::Easygoing).name,
+            "from the asset"
+        );
+        let bye = engine.bye(personality::Personality::Easygoing);
+        assert_eq!(bye.id, BYE_DIALOG);
+        assert_eq!(bye.name, "passenger");
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn requirements_need_every_entry_present_and_every_negation_absent() {
+        let present = strings(&["met_before", "late"]);
+
+        assert!(requirements_met(&[], &present));
+        assert!(requirements_met(
+            &strings(&["met_before", "late"]),
+            &present
+        ));
+        assert!(!requirements_met(
+            &strings(&["met_before", "tipped"]),
+            &present
+        ));
+        assert!(requirements_met(&strings(&["!tipped"]), &present));
+        assert!(!requirements_met(
+            &strings(&["met_before", "!late"]),
+            &present
+        ));
+    }
+
+    #[test]
+    fn changes_add_entries_once_and_negations_remove_them() {
+        let mut present = strings(&["late"]);
+
+        apply_changes(
+            &strings(&["met_before", "met_before", "!late"]),
+            &mut present,
+        );
+        assert_eq!(present, ["met_before"]);
+        apply_changes(&strings(&["!missing"]), &mut present);
+        assert_eq!(present, ["met_before"]);
+    }
+
+    #[test]
+    fn choosing_applies_the_choice_and_follows_next_id() {
+        let mut greeting = dialog("greeting", "greeting", &[]);
+        greeting.actions.next_id = String::from("small talk");
+        let mut world = World::new();
+        world.insert_resource(registry(vec![
+            greeting.clone(),
+            dialog("small talk", "regular", &["met_before"]),
+            dialog("small talk", "stranger", &[]),
+            dialog("goodbye", "goodbye", &[]),
+        ]));
+        world.insert_resource(OccuredEvents(vec![]));
+        world.insert_resource(Posessions(vec![String::from("umbrella")]));
+        let mut state = SystemState::<DialogEngine>::new(&mut world);
+        let mut engine = state.get_mut(&mut world);
+
+        // Without a next_id of its own the choice follows the dialog's
+        let mut hello = choice("hello", language("Hello", "Hola"));
+        hello.dialog.actions.events_changed_on_enter = strings(&["met_before"]);
+        hello.dialog.actions.items_changed_on_exit = strings(&["!umbrella"]);
+        let next = engine.choose(&greeting, &hello).unwrap();
+        assert_eq!(next.name, "regular");
+        assert_eq!(engine.events.0, ["met_before"]);
+        assert!(engine.posessions.0.is_empty());
+
+        let mut leave = choice("leave", language("Bye", "Adiós"));
+        leave.dialog.actions.next_id = String::from("goodbye");
+        assert_eq!(engine.choose(&greeting, &leave).unwrap().id, "goodbye");
+
+        let mut unknown = choice("unknown", language("?", "?"));
+        unknown.dialog.actions.next_id = String::from("missing");
+        assert!(engine.choose(&greeting, &unknown).is_none());
+        assert!(engine
+            .choose(&next, &choice("end", language("", "")))
+            .is_none());
+    }
+
+    #[test]
+    fn showing_the_same_dialog_again_runs_its_actions_again() {
+        let mut world = World::new();
+        world.insert_resource(registry(vec![]));
+        world.insert_resource(OccuredEvents(vec![]));
+        world.insert_resource(Posessions(vec![]));
+        world.insert_resource(DialogMessage::default());
+        let mut system = IntoSystem::into_system(dialog_actions_system);
+        system.initialize(&mut world);
+
+        let mut bye = dialog(BYE_DIALOG, "passenger", &[]);
+        bye.actions.items_changed_on_enter = strings(&["coin"]);
+        bye.actions.items_changed_on_exit = strings(&["!coin"]);
+        let entered = |world: &World| {
+            world
+                .resource::<Posessions>()
+                .0
+                .contains(&String::from("coin"))
+        };
+
+        world
+            .resource_mut::<DialogMessage>()
+            .show(Some(bye.clone()));
+        system.run((), &mut world);
+        assert!(entered(&world));
+
+        // The next rider's bye: the first one's exit runs, then the enter again
+        world.resource_mut::<Posessions>().0.clear();
+        world.resource_mut::<DialogMessage>().show(Some(bye));
+        system.run((), &mut world);
+        assert!(entered(&world));
+
+        // Nothing new shown, nothing runs
+        world.resource_mut::<Posessions>().0.clear();
+        system.run((), &mut world);
+        assert!(!entered(&world));
+    }
+}
@@ -0,0 +1,300 @@
+use crate::broadphase;
+use crate::rng;
+use crate::structured_dialog;
+use crate::tuning::GameTuning;
+use crate::AppState;
+use crate::Car;
+use crate::GameState;
+use crate::PlayerCar;
+use crate::PlayerMarker;
+use bevy::math::bounding::{Aabb2d, BoundingVolume};
+use bevy::prelude::*;
+use rand::Rng;
+
+/// A lane change in progress, the car signals first and then slides over.
+#[derive(Clone, Debug)]
+pub struct LaneChange {
+    pub target_y: f32,
+    pub signal: Timer,
+}
+
+/// The blinker on an NPC car, a child of the car's entity.
+#[derive(Component)]
+pub struct TurnSignal;
+
+pub struct TrafficPlugin;
+
+impl Plugin for TrafficPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (traffic_system, turn_signal_system)
+                .chain()
+                .before(crate::road_system)
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+pub fn turn_signal() -> impl Bundle {
+    (
+        GameState,
+        TurnSignal,
+        Sprite::from_color(Color::srgb(1.0, 0.65, 0.0), Vec2::new(8., 6.)),
+        Transform::from_xyz(0., 0., 1.),
+        Visibility::Hidden,
+    )
+}
+
+// Where a car is at the start of the frame, so every driver sees the same road.
+struct Position {
+    entity: Entity,
+    aabb: Aabb2d,
+    target_y: Option<f32>,
+    /// How fast it moves along the road, stopped cars are 0
+    speed: f32,
+}
+
+impl Position {
+    // Cars between lanes, or signalling to move over, are in both.
+    fn occupies(&self, lane_y: f32, lane_height: f32) -> bool {
+        (self.aabb.center().y - lane_y).abs() < lane_height
+            || self
+                .target_y
+                .is_some_and(|target_y| (target_y - lane_y).abs() < lane_height)
+    }
+}
+
+/// NPC drivers keep their distance, slow down behind slower cars and the
+/// taxi, and now and then change lanes, mostly to get past someone slow.
+/// `road_system` then moves them at the speed picked here.
+fn traffic_system(
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+    dialog_message: Res<structured_dialog::DialogMessage>,
+    mut game_rng: ResMut<rng::GameRng>,
+    player_query: Query<(&Sprite, &PlayerCar), With<PlayerMarker>>,
+    mut car_query: Query<(Entity, &mut Transform, &mut Car)>,
+) {
+    if let Some(dialog) = &dialog_message.dialog {
+        if dialog.choices.is_some() {
+            return;
+        }
+    }
+
+    let Ok((player_sprite, player_car)) = player_query.get_single() else {
+        return;
+    };
+    let player_velocity = if player_sprite.flip_x {
+        -tuning.speed_x * player_car.speed_coeff
+    } else {
+        tuning.speed_x * player_car.speed_coeff
+    };
+
+    let positions: Vec<Position> = car_query
+        .iter()
+        .map(|(entity, _, npc_car)| Position {
+            entity,
+            aabb: npc_car.aabb,
+            target_y: npc_car
+                .lane_change
+                .as_ref()
+                .map(|lane_change| lane_change.target_y),
+            speed: if stopped(npc_car) { 0. } else { npc_car.speed },
+        })
+        .collect();
+    let nearby = broadphase::SortedByX::new(positions.iter().map(|other| (other.aabb, other)));
+    let widest = positions
+        .iter()
+        .map(|other| other.aabb.half_size().x)
+        .fold(0., f32::max);
+
+    let traffic = &tuning.traffic;
+    let lane_height = tuning.lane_height;
+    let delta = time.delta_secs();
+    for (entity, mut npc_car_transform, mut npc_car) in car_query.iter_mut() {
+        if stopped(&npc_car) {
+            continue;
+        }
+
+        let center = npc_car.aabb.center();
+        // Cars in the top lanes drive left
+        let direction = if center.y > 0. { -1. } else { 1. };
+        let half_width = npc_car.aabb.half_size().x;
+
+        // The closest car ahead in the lane, the taxi counts as one
+        let ahead = |aabb: &Aabb2d| {
+            (aabb.center().x - center.x) * direction - half_width - aabb.half_size().x
+        };
+        // Cars further away than the following distance don't slow this one down
+        let reach = traffic.following_distance + half_width + widest;
+        let (gap, leader_speed) = nearby
+            .near(center.x, reach)
+            .filter(|other| other.entity != entity && other.occupies(center.y, lane_height))
+            .map(|other| (ahead(&other.aabb), other.speed))
+            .chain(
+                ((player_car.aabb.center().y - center.y).abs() < lane_height).then(|| {
+                    (
+                        ahead(&player_car.aabb),
+                        (player_velocity * direction).max(0.),
+                    )
+                }),
+            )
+            .filter(|(gap, _)| *gap > -half_width)
+            .fold((f32::INFINITY, f32::INFINITY), |closest, next| {
+                if next.0 < closest.0 {
+                    next
+                } else {
+                    closest
+                }
+            });
+
+        let target_speed = if gap < traffic.min_gap {
+            0.
+        } else if gap < traffic.following_distance {
+            npc_car.cruise_speed.min(leader_speed)
+        } else {
+            npc_car.cruise_speed
+        };
+        npc_car.speed = if target_speed < npc_car.speed {
+            (npc_car.speed - traffic.braking * delta).max(target_speed)
+        } else {
+            (npc_car.speed + traffic.acceleration * delta).min(target_speed)
+        };
+
+        if npc_car.lane_change.is_none() {
+            let stuck = gap < traffic.following_distance && leader_speed < npc_car.cruise_speed;
+            let chance = if stuck {
+                traffic.lane_change_chance
+            } else {
+                traffic.wander_chance
+            };
+            if game_rng.traffic.gen::<f32>() < chance * delta {
+                // Each direction has two lanes, an inner one and an outer one
+                let target_y = if center.y.abs() < lane_height {
+                    center.y.signum() * 1.5 * lane_height
+                } else {
+                    center.y.signum() * 0.5 * lane_height
+                };
+                let clearance = traffic.following_distance + 2. * half_width;
+                let lane_clear = !nearby
+                    .near(center.x, clearance)
+                    .any(|other| other.entity != entity && other.occupies(target_y, lane_height))
+                    && ((player_car.aabb.center().y - target_y).abs() >= lane_height
+                        || (player_car.aabb.center().x - center.x).abs() > clearance);
+                if lane_clear {
+                    npc_car.lane_change = Some(LaneChange {
+                        target_y,
+                        signal: Timer::from_seconds(traffic.signal_seconds, TimerMode::Once),
+                    });
+                }
+            }
+        }
+
+        let Some(lane_change) = &mut npc_car.lane_change else {
+            continue;
+        };
+        lane_change.signal.tick(time.delta());
+        if !lane_change.signal.finished() {
+            continue;
+        }
+        let target_y = lane_change.target_y;
+        let step = lane_height / traffic.lane_change_seconds * delta;
+        let dy = (target_y - npc_car_transform.translation.y).clamp(-step, step);
+        npc_car_transform.translation.y += dy;
+        npc_car.aabb.translate_by(Vec2::new(0.0, dy));
+        if (target_y - npc_car_transform.translation.y).abs() < f32::EPSILON {
+            npc_car.lane_change = None;
+        }
+    }
+}
+
+// Crashed cars and cars pushing against the taxi don't drive on their own.
+fn stopped(npc_car: &Car) -> bool {
+    npc_car.intersects_npc || npc_car.intersects_player
+}
+
+/// Blinks the turn signal on the front corner of the side the car is moving to.
+fn turn_signal_system(
+    time: Res<Time>,
+    car_query: Query<(&Transform, &Car), Without<TurnSignal>>,
+    mut signal_query: Query<(&Parent, &mut Transform, &mut Visibility), With<TurnSignal>>,
+) {
+    let blink_on = (time.elapsed_secs() * 3.).fract() < 0.5;
+    for (parent, mut signal_transform, mut visibility) in signal_query.iter_mut() {
+        let Ok((npc_car_transform, npc_car)) = car_query.get(parent.get()) else {
+            continue;
+        };
+        let Some(lane_change) = &npc_car.lane_change else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        let half_size = npc_car.aabb.half_size();
+        let front = if npc_car_transform.translation.y > 0. {
+            -1.
+        } else {
+            1.
+        };
+        let side = (lane_change.target_y - npc_car_transform.translation.y).signum();
+        signal_transform.translation.x = front * (half_size.x - 6.);
+        signal_transform.translation.y = side * (half_size.y - 5.);
+        *visibility = if blink_on {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::*;
+    use crate::structured_dialog::GameScript;
+    use crate::CarMarker;
+
+    #[test]
+    fn fast_car_brakes_behind_a_slow_one_then_passes() {
+        let mut app = headless_app(&GameScript::default(), 7);
+        stop_spawning(&mut app);
+        {
+            let mut tuning = app.world_mut().resource_mut::<GameTuning>();
+            tuning.traffic.lane_change_chance = 0.;
+            tuning.traffic.wander_chance = 0.;
+        }
+
+        // Both in the inner top lane, driving left
+        let y = GameTuning::default().lane_height / 2.;
+        let mut spawn_car = |x: f32, speed: f32| {
+            app.world_mut()
+                .spawn((
+                    CarMarker,
+                    Car::new(
+                        Aabb2d::new(Vec2::new(x, y), Vec2::new(89. / 2., 53. / 2.)),
+                        speed,
+                    ),
+                    Transform::from_xyz(x, y, 0.),
+                ))
+                .id()
+        };
+        let slow = spawn_car(50., 50.);
+        let fast = spawn_car(250., 250.);
+
+        run(&mut app, 90);
+        let fast_car = app.world().get::<Car>(fast).unwrap().clone();
+        let slow_car = app.world().get::<Car>(slow).unwrap();
+        assert!(!fast_car.intersects_npc && !slow_car.intersects_npc);
+        assert!(fast_car.speed <= slow_car.speed);
+        assert!(fast_car.aabb.min.x > slow_car.aabb.max.x);
+
+        app.world_mut()
+            .resource_mut::<GameTuning>()
+            .traffic
+            .lane_change_chance = 1000.;
+        run(&mut app, 90);
+        let fast_y = app.world().get::<Transform>(fast).unwrap().translation.y;
+        assert_eq!(fast_y, 1.5 * GameTuning::default().lane_height);
+        assert!(!app.world().get::<Car>(fast).unwrap().intersects_npc);
+    }
+}
@@ -0,0 +1,745 @@
+use crate::difficulty::{Difficulties, Difficulty, DifficultyPreset};
+use crate::meter;
+use crate::personality::{Personalities, Personality};
+use crate::rating::Standing;
+use crate::save::Profile;
+use crate::PlayerHealth;
+use crate::PlayerMarker;
+use crate::Ride;
+use crate::SpawnThingTimer;
+use crate::MAX_SEATS;
+use bevy::prelude::*;
+use bevy_common_assets::json::JsonAssetPlugin;
+use std::time::Duration;
+
+/// Gameplay numbers loaded from `tuning.json`. Missing fields keep the
+/// built-in value, and the file is re-read whenever it changes on disk so
+/// rides and rounds can be balanced while the game runs.
+#[derive(serde::Deserialize, Asset, TypePath, Resource, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct GameTuning {
+    /// Distance scrolled per second at full speed, 1000 is one km
+    pub speed_x: f32,
+    pub lane_height: f32,
+    pub time_limit_seconds: f32,
+    pub fare_per_km: f32,
+    /// Tip every passenger starts from, before upgrades and the tip tables
+    pub base_tip_percentage: f32,
+    /// Tip percentage by km driven past the stop
+    pub dropoff_tips: TipTable,
+    /// Tip percentage by trip time over the fastest possible trip time
+    pub trip_time_tips: TipTable,
+    pub npc_car_speed: SpeedRange,
+    /// Seconds between rolls for a new car, passenger or shop
+    pub spawn_seconds: f32,
+    pub required_earnings: EarningsCurve,
+    pub difficulties: Difficulties,
+    pub fuel: FuelTuning,
+    pub damage: DamageTuning,
+    pub oil: OilTuning,
+    pub traffic: TrafficTuning,
+    pub pooling: PoolingTuning,
+    pub personalities: Personalities,
+    pub patience: PatienceTuning,
+    pub landmarks: LandmarkTuning,
+    pub u_turn: UTurnTuning,
+    pub meter: MeterTuning,
+    pub receipt: ReceiptTuning,
+    pub rating: RatingTuning,
+}
+
+impl Default for GameTuning {
+    fn default() -> Self {
+        Self {
+            speed_x: 300.,
+            lane_height: 70.,
+            time_limit_seconds: 60.,
+            fare_per_km: 7.,
+            base_tip_percentage: 10.,
+            dropoff_tips: TipTable {
+                steps: vec![
+                    TipStep::new(0.25, 3.5),
+                    TipStep::new(0.5, 0.5),
+                    TipStep::new(0.75, -2.),
+                    TipStep::new(1.75, -4.),
+                    TipStep::new(2.0, -6.),
+                    TipStep::new(3.0, -8.),
+                ],
+                otherwise: -10.,
+            },
+            trip_time_tips: TipTable {
+                steps: vec![
+                    TipStep::new(1.1, 4.),
+                    TipStep::new(1.2, 2.5),
+                    TipStep::new(1.3, 0.5),
+                    TipStep::new(1.4, -1.),
+                    TipStep::new(1.5, -3.),
+                    TipStep::new(1.6, -6.),
+                ],
+                otherwise: -10.,
+            },
+            npc_car_speed: SpeedRange {
+                min: 200.,
+                max: 290.,
+            },
+            spawn_seconds: 0.2,
+            required_earnings: EarningsCurve {
+                first_round: 50.,
+                base: 49.,
+                steps: vec![
+                    CurveStep {
+                        below_cycle: 10,
+                        offset: 0.,
+                        per_cycle: 0.02,
+                    },
+                    CurveStep {
+                        below_cycle: 30,
+                        offset: 0.2,
+                        per_cycle: 0.002,
+                    },
+                ],
+                max_exponent: 0.258,
+            },
+            difficulties: Difficulties::default(),
+            fuel: FuelTuning {
+                tank: 30.,
+                litres_per_km: 1.,
+                price_per_litre: 1.,
+                tow_fee: 40.,
+                tow_litres: 10.,
+            },
+            damage: DamageTuning {
+                per_crash: 25.,
+                top_speed_loss: 0.4,
+                tip_loss: 10.,
+                repair_price: 0.5,
+            },
+            oil: OilTuning {
+                chance: 0.02,
+                chance_per_cycle: 0.01,
+                max_chance: 0.15,
+                skid_seconds: 0.75,
+                tip_loss: 3.,
+            },
+            traffic: TrafficTuning {
+                following_distance: 120.,
+                min_gap: 30.,
+                acceleration: 120.,
+                braking: 400.,
+                lane_change_chance: 0.5,
+                wander_chance: 0.05,
+                signal_seconds: 0.8,
+                lane_change_seconds: 0.6,
+            },
+            pooling: PoolingTuning {
+                seats: 3,
+                discount: 0.2,
+            },
+            personalities: Personalities::default(),
+            patience: PatienceTuning {
+                wait_seconds: 25.,
+                warn_at: 0.4,
+                declines_allowed: 2,
+                decline_penalty: 5.,
+                max_penalty: 25.,
+            },
+            landmarks: LandmarkTuning { dropoff_reach: 80. },
+            u_turn: UTurnTuning {
+                seconds: 1.5,
+                speed_kept: 0.5,
+            },
+            meter: MeterTuning {
+                flag_drop: 2.,
+                waiting_per_minute: 6.,
+            },
+            receipt: ReceiptTuning {
+                star_thresholds: vec![-10., -5., 0., 5.],
+            },
+            rating: RatingTuning {
+                min_rides: 5,
+                vip_from: 4.5,
+                vip_chance: 0.3,
+                vip_fares: 2.,
+                poor_below: 2.5,
+                poor_skip_chance: 0.5,
+            },
+        }
+    }
+}
+
+/// Adjustments for values below each `below`, checked in order.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct TipTable {
+    pub steps: Vec<TipStep>,
+    pub otherwise: f32,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct TipStep {
+    pub below: f32,
+    pub adjustment: f32,
+}
+
+impl TipStep {
+    fn new(below: f32, adjustment: f32) -> Self {
+        Self { below, adjustment }
+    }
+}
+
+impl TipTable {
+    pub fn adjustment(&self, value: f32) -> f32 {
+        self.steps
+            .iter()
+            .find(|step| value < step.below)
+            .map(|step| step.adjustment)
+            .unwrap_or(self.otherwise)
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct SpeedRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct FuelTuning {
+    /// Litres in a full tank, every run starts full
+    pub tank: f32,
+    /// Litres per km at full speed
+    pub litres_per_km: f32,
+    pub price_per_litre: f32,
+    /// Taken from the total earnings when the taxi runs dry
+    pub tow_fee: f32,
+    /// Litres the tow truck leaves in the tank
+    pub tow_litres: f32,
+}
+
+impl FuelTuning {
+    /// Litres used to drive `distance` km, slow driving burns half as much per km.
+    pub fn burn(&self, distance: f32, speed_coeff: f32) -> f32 {
+        distance * self.litres_per_km * (0.5 + 0.5 * speed_coeff.min(1.))
+    }
+
+    /// Coins to fill the tank up from `fuel` litres.
+    pub fn fill_price(&self, fuel: f32) -> f32 {
+        ((self.tank - fuel).max(0.) * self.price_per_litre).ceil()
+    }
+}
+
+/// Damage goes from 0 for a new taxi to 100 for a wreck.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct DamageTuning {
+    /// Damage from a crash at a relative speed of `speed_x`, faster crashes do more
+    pub per_crash: f32,
+    /// Share of the top speed lost by a wreck
+    pub top_speed_loss: f32,
+    /// Tip percentage lost by a wreck
+    pub tip_loss: f32,
+    /// Coins per point of damage repaired
+    pub repair_price: f32,
+}
+
+impl DamageTuning {
+    /// Multiplies the top speed while damaged.
+    pub fn top_speed(&self, damage: f32) -> f32 {
+        1. - self.top_speed_loss * damage / 100.
+    }
+
+    pub fn tip_penalty(&self, damage: f32) -> f32 {
+        self.tip_loss * damage / 100.
+    }
+
+    pub fn repair_price(&self, damage: f32) -> f32 {
+        (damage * self.repair_price).ceil()
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct OilTuning {
+    /// Chance of an oil puddle each time the spawn timer goes off in the first round
+    pub chance: f32,
+    /// Added to `chance` for every round completed
+    pub chance_per_cycle: f32,
+    pub max_chance: f32,
+    /// Seconds the taxi slides without steering or brakes after hitting a puddle
+    pub skid_seconds: f32,
+    /// Tip percentage every passenger on board takes off for the scare
+    pub tip_loss: f32,
+}
+
+impl OilTuning {
+    pub fn chance(&self, cycles_completed: u32) -> f32 {
+        (self.chance + self.chance_per_cycle * cycles_completed as f32).min(self.max_chance)
+    }
+}
+
+/// How NPC drivers behave. Gaps are between bumpers, speeds are in the same
+/// units as `npc_car_speed`.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct TrafficTuning {
+    /// Closer than this a car slows to the speed of the car ahead
+    pub following_distance: f32,
+    /// Closer than this a car brakes to a stop
+    pub min_gap: f32,
+    pub acceleration: f32,
+    pub braking: f32,
+    /// Chance per second of passing a slower car ahead when the other lane is clear
+    pub lane_change_chance: f32,
+    /// Chance per second of changing lanes for no reason
+    pub wander_chance: f32,
+    /// Seconds the turn signal blinks before the car moves over
+    pub signal_seconds: f32,
+    pub lane_change_seconds: f32,
+}
+
+/// Ride sharing. Everyone in a shared taxi pays a discounted fare.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct PoolingTuning {
+    /// Passengers the taxi takes at once, up to `MAX_SEATS`
+    pub seats: usize,
+    /// Share of the fare taken off for sharing the ride
+    pub discount: f32,
+}
+
+impl PoolingTuning {
+    pub fn pooled_fare(&self, fare: f32) -> f32 {
+        (fare * (1. - self.discount)).ceil()
+    }
+}
+
+/// Passengers waiting at the curb, and what turning them down does.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct PatienceTuning {
+    /// Seconds on screen before a passenger gives up, scaled by their personality
+    pub wait_seconds: f32,
+    /// Share of the wait left when the warning icon shows up
+    pub warn_at: f32,
+    /// Fares that can be turned down in a row without talk getting around
+    pub declines_allowed: u32,
+    /// Tip percentage lost per fare declined after that, paid rides win it back
+    pub decline_penalty: f32,
+    pub max_penalty: f32,
+}
+
+/// Where passengers get out.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct LandmarkTuning {
+    /// How far from a destination's landmark the taxi can stop and still let
+    /// the passenger out
+    pub dropoff_reach: f32,
+}
+
+/// Turning the taxi around while it's moving.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct UTurnTuning {
+    /// Seconds the taxi stands still swinging around
+    pub seconds: f32,
+    /// Share of the speed the taxi comes out of the turn with
+    pub speed_kept: f32,
+}
+
+/// The fare meter's charges on top of `fare_per_km`, scaled by the difficulty
+/// like the rest of the fare.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct MeterTuning {
+    /// Charged as soon as the passenger gets in
+    pub flag_drop: f32,
+    /// Charged while the taxi is held up by traffic or a U-turn with the
+    /// passenger on board
+    pub waiting_per_minute: f32,
+}
+
+/// The star rating on a ride's receipt.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct ReceiptTuning {
+    /// Tip adjustments adding up to at least each of these earn a star on
+    /// top of the first one, in increasing order
+    pub star_thresholds: Vec<f32>,
+}
+
+impl ReceiptTuning {
+    pub fn stars(&self, adjustments: f32) -> u32 {
+        let earned = self
+            .star_thresholds
+            .iter()
+            .filter(|threshold| adjustments >= **threshold)
+            .count() as u32;
+        (1 + earned).min(5)
+    }
+}
+
+/// What the driver's average rating unlocks, or costs.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct RatingTuning {
+    /// Paid rides before the rating counts for anything
+    pub min_rides: u32,
+    /// Average stars from which VIPs show up
+    pub vip_from: f32,
+    /// Share of passengers who are VIPs once they do
+    pub vip_chance: f32,
+    /// Multiplies a VIP's fare
+    pub vip_fares: f32,
+    /// Average stars below which fewer passengers show up
+    pub poor_below: f32,
+    /// Share of passengers who don't wave down a poorly rated taxi
+    pub poor_skip_chance: f32,
+}
+
+impl RatingTuning {
+    pub fn standing(&self, profile: &Profile) -> Standing {
+        match profile.rating() {
+            Some(_) if profile.rated_rides < self.min_rides => Standing::Regular,
+            Some(rating) if rating >= self.vip_from => Standing::Vip,
+            Some(rating) if rating < self.poor_below => Standing::Poor,
+            _ => Standing::Regular,
+        }
+    }
+}
+
+/// Earnings needed to finish a round: `base^(1 + x)` where `x` grows with
+/// the rounds completed, capped at `max_exponent`.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct EarningsCurve {
+    pub first_round: f32,
+    pub base: f32,
+    pub steps: Vec<CurveStep>,
+    pub max_exponent: f32,
+}
+
+/// Until `below_cycle`, `x = offset + cycles * per_cycle`.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct CurveStep {
+    pub below_cycle: u32,
+    pub offset: f32,
+    pub per_cycle: f32,
+}
+
+impl EarningsCurve {
+    pub fn required(&self, cycles_completed: u32) -> f32 {
+        if cycles_completed == 0 {
+            return self.first_round;
+        }
+        let x = self
+            .steps
+            .iter()
+            .find(|step| cycles_completed < step.below_cycle)
+            .map(|step| step.offset + (cycles_completed as f32) * step.per_cycle)
+            .unwrap_or(self.max_exponent);
+        self.base.powf(1. + x).ceil()
+    }
+}
+
+impl GameTuning {
+    pub fn preset(&self, difficulty: Difficulty) -> &DifficultyPreset {
+        self.difficulties.get(difficulty)
+    }
+
+    pub fn time_limit(&self, difficulty: Difficulty) -> Duration {
+        Duration::from_secs_f32(self.time_limit_seconds * self.preset(difficulty).time_limit)
+    }
+
+    /// The quote for a ride of `distance` km, what the meter reads driving
+    /// straight there without stopping.
+    pub fn fare(&self, distance: f32, difficulty: Difficulty) -> f32 {
+        ((self.meter.flag_drop + distance * self.fare_per_km) * self.preset(difficulty).fares)
+            .ceil()
+    }
+
+    pub fn meter_reading(&self, ride: &Ride, difficulty: Difficulty) -> meter::Reading {
+        let fares =
+            self.preset(difficulty).fares * if ride.vip { self.rating.vip_fares } else { 1. };
+        let flag_drop = self.meter.flag_drop * fares;
+        let distance = ride.meter.km * self.fare_per_km * fares;
+        let waiting = ride.meter.waiting / 60. * self.meter.waiting_per_minute * fares;
+        let total = (flag_drop + distance + waiting).ceil();
+        meter::Reading {
+            flag_drop,
+            distance,
+            waiting,
+            fare: if ride.pooled {
+                self.pooling.pooled_fare(total)
+            } else {
+                total
+            },
+        }
+    }
+
+    /// The tip percentage every passenger starts from, before upgrades.
+    pub fn tip_percentage(&self, difficulty: Difficulty) -> f32 {
+        self.base_tip_percentage + self.preset(difficulty).tips
+    }
+
+    pub fn spawn_interval(&self, difficulty: Difficulty) -> Duration {
+        Duration::from_secs_f32(self.spawn_seconds / self.preset(difficulty).traffic)
+    }
+
+    /// Earnings needed to finish the round after `cycles_completed` rounds.
+    pub fn earnings_target(&self, cycles_completed: u32, difficulty: Difficulty) -> f32 {
+        if self.preset(difficulty).flat_target {
+            self.required_earnings.required(0)
+        } else {
+            self.required_earnings.required(cycles_completed)
+        }
+    }
+
+    /// Damage from a crash where the taxi and the other car close in at `relative_speed`.
+    pub fn crash_damage(&self, relative_speed: f32) -> f32 {
+        relative_speed / self.speed_x * self.damage.per_crash
+    }
+
+    /// Lists every value the game can't run with.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems = vec![];
+        for (name, value) in [
+            ("speed_x", self.speed_x),
+            ("lane_height", self.lane_height),
+            ("time_limit_seconds", self.time_limit_seconds),
+            ("spawn_seconds", self.spawn_seconds),
+            (
+                "required_earnings.first_round",
+                self.required_earnings.first_round,
+            ),
+            ("required_earnings.base", self.required_earnings.base),
+            ("fuel.tank", self.fuel.tank),
+            ("fuel.price_per_litre", self.fuel.price_per_litre),
+            (
+                "traffic.lane_change_seconds",
+                self.traffic.lane_change_seconds,
+            ),
+            ("patience.wait_seconds", self.patience.wait_seconds),
+            ("landmarks.dropoff_reach", self.landmarks.dropoff_reach),
+            ("u_turn.seconds", self.u_turn.seconds),
+        ] {
+            if !(value.is_finite() && value > 0.) {
+                problems.push(format!("{} must be above 0, got {}", name, value));
+            }
+        }
+        if !(self.fare_per_km.is_finite() && self.fare_per_km >= 0.) {
+            problems.push(format!(
+                "fare_per_km can't be negative, got {}",
+                self.fare_per_km
+            ));
+        }
+        for (name, value) in [
+            ("fuel.litres_per_km", self.fuel.litres_per_km),
+            ("fuel.tow_fee", self.fuel.tow_fee),
+            ("damage.per_crash", self.damage.per_crash),
+            ("damage.tip_loss", self.damage.tip_loss),
+            ("damage.repair_price", self.damage.repair_price),
+            ("oil.chance_per_cycle", self.oil.chance_per_cycle),
+            ("oil.skid_seconds", self.oil.skid_seconds),
+            ("oil.tip_loss", self.oil.tip_loss),
+            ("traffic.min_gap", self.traffic.min_gap),
+            ("traffic.acceleration", self.traffic.acceleration),
+            ("traffic.braking", self.traffic.braking),
+            (
+                "traffic.lane_change_chance",
+                self.traffic.lane_change_chance,
+            ),
+            ("traffic.wander_chance", self.traffic.wander_chance),
+            ("traffic.signal_seconds", self.traffic.signal_seconds),
+            ("patience.decline_penalty", self.patience.decline_penalty),
+            ("patience.max_penalty", self.patience.max_penalty),
+            ("meter.flag_drop", self.meter.flag_drop),
+            ("meter.waiting_per_minute", self.meter.waiting_per_minute),
+            ("rating.vip_fares", self.rating.vip_fares),
+        ] {
+            if !(value.is_finite() && value >= 0.) {
+                problems.push(format!("{} can't be negative, got {}", name, value));
+            }
+        }
+        if !(self.fuel.tow_litres > 0. && self.fuel.tow_litres <= self.fuel.tank) {
+            problems.push(format!(
+                "fuel.tow_litres needs to be above 0 and fit in the tank, got {}",
+                self.fuel.tow_litres
+            ));
+        }
+        if !(self.damage.top_speed_loss >= 0. && self.damage.top_speed_loss < 1.) {
+            problems.push(format!(
+                "damage.top_speed_loss needs 0 <= loss < 1, got {}",
+                self.damage.top_speed_loss
+            ));
+        }
+        for (name, value) in [
+            ("oil.chance", self.oil.chance),
+            ("oil.max_chance", self.oil.max_chance),
+            ("pooling.discount", self.pooling.discount),
+            ("patience.warn_at", self.patience.warn_at),
+            ("u_turn.speed_kept", self.u_turn.speed_kept),
+            ("rating.vip_chance", self.rating.vip_chance),
+            ("rating.poor_skip_chance", self.rating.poor_skip_chance),
+        ] {
+            if !(0. ..=1.).contains(&value) {
+                problems.push(format!("{} must be between 0 and 1, got {}", name, value));
+            }
+        }
+        if !(1..=MAX_SEATS).contains(&self.pooling.seats) {
+            problems.push(format!(
+                "pooling.seats needs 1 to {} seats, got {}",
+                MAX_SEATS, self.pooling.seats
+            ));
+        }
+        if !(self.traffic.following_distance.is_finite()
+            && self.traffic.following_distance > self.traffic.min_gap)
+        {
+            problems.push(format!(
+                "traffic.following_distance needs to be above min_gap, got {}",
+                self.traffic.following_distance
+            ));
+        }
+        for difficulty in Difficulty::ALL {
+            let preset = self.preset(difficulty);
+            if !(preset.time_limit > 0. && preset.traffic > 0. && preset.fares >= 0.) {
+                problems.push(format!(
+                    "difficulties.{} needs time_limit and traffic above 0 and fares of at least 0",
+                    difficulty.id()
+                ));
+            }
+        }
+        for personality in Personality::ALL {
+            let traits = self.personalities.get(personality);
+            if !(traits.patience > 0. && traits.dropoff_tips >= 0. && traits.trip_time_tips >= 0.) {
+                problems.push(format!(
+                    "personalities.{} needs patience above 0 and tip weights of at least 0",
+                    personality.id().replace(' ', "_")
+                ));
+            }
+        }
+        if !(self.npc_car_speed.min >= 0. && self.npc_car_speed.min < self.npc_car_speed.max) {
+            problems.push(format!(
+                "npc_car_speed needs 0 <= min < max, got {}..{}",
+                self.npc_car_speed.min, self.npc_car_speed.max
+            ));
+        }
+        for (name, table) in [
+            ("dropoff_tips", &self.dropoff_tips),
+            ("trip_time_tips", &self.trip_time_tips),
+        ] {
+            if table.steps.windows(2).any(|w| w[0].below >= w[1].below) {
+                problems.push(format!("{} steps must be in increasing order", name));
+            }
+        }
+        if !(1. <= self.rating.poor_below
+            && self.rating.poor_below <= self.rating.vip_from
+            && self.rating.vip_from <= 5.)
+        {
+            problems.push(format!(
+                "rating needs 1 <= poor_below <= vip_from <= 5, got {} and {}",
+                self.rating.poor_below, self.rating.vip_from
+            ));
+        }
+        if self.receipt.star_thresholds.len() > 4
+            || self
+                .receipt
+                .star_thresholds
+                .windows(2)
+                .any(|w| w[0] >= w[1])
+        {
+            problems.push(String::from(
+                "receipt.star_thresholds needs at most 4 steps in increasing order",
+            ));
+        }
+        if self
+            .required_earnings
+            .steps
+            .windows(2)
+            .any(|w| w[0].below_cycle >= w[1].below_cycle)
+        {
+            problems.push(String::from(
+                "required_earnings steps must be in increasing order",
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join(", "))
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+#[allow(dead_code)]
+pub struct TuningHandle(pub Handle<GameTuning>);
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(JsonAssetPlugin::<GameTuning>::new(&[".json"]))
+            .insert_resource(GameTuning::default())
+            .add_systems(Startup, load_tuning)
+            .add_systems(Update, tuning_reload_system);
+    }
+}
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load("tuning.json")));
+}
+
+/// Swaps in `tuning.json` when it loads or changes, keeping the current
+/// values if the new ones don't validate. The road, the taxi and the traffic
+/// are laid out on `lane_height`, so it only changes between runs.
+fn tuning_reload_system(
+    mut asset_events: EventReader<AssetEvent<GameTuning>>,
+    tuning_assets: Res<Assets<GameTuning>>,
+    mut tuning: ResMut<GameTuning>,
+    mut player_data: ResMut<PlayerHealth>,
+    mut spawn_thing_timer: ResMut<SpawnThingTimer>,
+    player_query: Query<(), With<PlayerMarker>>,
+) {
+    for event in asset_events.read() {
+        let id = match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => id,
+            _ => continue,
+        };
+        let Some(new_tuning) = tuning_assets.get(*id) else {
+            continue;
+        };
+        if let Err(e) = new_tuning.validate() {
+            error!("Ignoring tuning.json: {}", e);
+            continue;
+        }
+
+        let lane_height = tuning.lane_height;
+        *tuning = new_tuning.clone();
+        if !player_query.is_empty() && tuning.lane_height != lane_height {
+            warn!("lane_height in tuning.json takes effect after a restart");
+            tuning.lane_height = lane_height;
+        }
+        let difficulty = player_data.difficulty;
+        player_data
+            .time_limit
+            .set_duration(tuning.time_limit(difficulty));
+        player_data.time_limit_required_earnings =
+            tuning.earnings_target(player_data.cycles_completed, difficulty);
+        spawn_thing_timer
+            .timer
+            .set_duration(tuning.spawn_interval(difficulty));
+        info!("Loaded tuning.json");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_tuning_matches_the_built_in_values() {
+        let shipped: GameTuning =
+            serde_json::from_str(include_str!("../assets/tuning.json")).unwrap();
+        assert_eq!(shipped.validate(), Ok(()));
+        assert_eq!(shipped, GameTuning::default());
+    }
+
+    #[test]
+    fn invalid_tuning_is_rejected() {
+        let mut tuning = GameTuning::default();
+        tuning.npc_car_speed.min = 300.;
+        tuning.speed_x = 0.;
+        let problems = tuning.validate().unwrap_err();
+        assert!(problems.contains("npc_car_speed"));
+        assert!(problems.contains("speed_x"));
+    }
+}